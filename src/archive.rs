@@ -0,0 +1,91 @@
+use std::io::{Read, Seek};
+use thiserror::Error;
+use zip::ZipArchive;
+use crate::deserialize::{self, ParamdefDeserializeError};
+use crate::Paramdex;
+
+/// An error when loading a [Paramdex] from an archive
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("Reading archive failed: {0}")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("Deserializing {entry} failed: {source}")]
+    Entry {
+        /// The name of the archive entry that failed, including its directory
+        entry: String,
+
+        /// The underlying error
+        source: Box<ParamdefDeserializeError>,
+    },
+}
+
+impl Paramdex {
+    /// Deserialize a whole Paramdex from a zip archive, such as an embedded dump of defs.
+    ///
+    /// Every entry with an `.xml` extension is deserialized as a def, regardless of the directory
+    /// it's in. Other entries are skipped.
+    pub fn deserialize_zip<R: Read + Seek>(reader: R) -> Result<Paramdex, ArchiveError> {
+        let mut archive = ZipArchive::new(reader)?;
+        let mut paramdex = Paramdex::empty();
+
+        for index in 0..archive.len() {
+            let file = archive.by_index(index)?;
+            let entry = file.name()?.into_owned();
+            if !file.is_file() || !entry.to_ascii_lowercase().ends_with(".xml") {
+                continue;
+            }
+            let paramdef = deserialize::deserialize_def_from_reader(file)
+                .map_err(|source| ArchiveError::Entry { entry, source: Box::new(source) })?;
+            paramdex.insert(paramdef);
+        }
+        Ok(paramdex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+    use crate::archive::ArchiveError;
+    use crate::Paramdex;
+
+    fn def_xml(param_type: &str) -> String {
+        format!(
+            "<PARAMDEF><ParamType>{}</ParamType><DataVersion>1</DataVersion><BigEndian>False</BigEndian>\
+            <Unicode>True</Unicode><FormatVersion>203</FormatVersion><Fields><Field Def=\"u8 a\" /></Fields></PARAMDEF>",
+            param_type,
+        )
+    }
+
+    fn zip(entries: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).expect("starts entry");
+            writer.write_all(contents.as_bytes()).expect("writes entry");
+        }
+        Cursor::new(writer.finish().expect("finishes archive").into_inner())
+    }
+
+    #[test]
+    fn deserialize_zip() {
+        let archive = zip(&[
+            ("Defs/A_PARAM_ST.xml", &def_xml("A_PARAM_ST")),
+            ("README.md", "Not a def"),
+            ("Defs/B_PARAM_ST.XML", &def_xml("B_PARAM_ST")),
+        ]);
+        let paramdex = Paramdex::deserialize_zip(archive).expect("deserializes");
+        assert!(paramdex.get_param_def("A_PARAM_ST").is_some());
+        assert!(paramdex.get_param_def("B_PARAM_ST").is_some());
+    }
+
+    #[test]
+    fn deserialize_zip_names_failed_entry() {
+        let archive = zip(&[("Defs/A_PARAM_ST.xml", &def_xml("A_PARAM_ST")), ("Defs/Broken.xml", "<PARAMDEF>")]);
+        match Paramdex::deserialize_zip(archive) {
+            Err(ArchiveError::Entry { entry, .. }) => assert_eq!(entry, "Defs/Broken.xml"),
+            other => panic!("Expected entry error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
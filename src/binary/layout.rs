@@ -0,0 +1,305 @@
+use crate::{DummyType, ParamDef, ParamdefEndian, ParamField, ParamFieldDef, ParamFieldType, ParamFieldTypeKind};
+
+/// Where a field is stored within a row
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct FieldOffset {
+    /// Offset of the field from the start of the row, in bytes. For bitfields, this is the
+    /// offset of the storage unit the field shares with neighbouring bitfields.
+    pub byte_offset: usize,
+
+    /// Size of the field in bytes. For bitfields, this is the size of the storage unit.
+    pub byte_size: usize,
+
+    /// For bitfields, where in the storage unit the field is stored
+    pub bits: Option<BitPosition>,
+}
+
+/// The position of a bitfield within its storage unit
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BitPosition {
+    /// Index of the lowest bit of the field, counting from the least significant bit of the
+    /// storage unit
+    pub offset: u8,
+
+    /// Number of bits in the field
+    pub size: u8,
+}
+
+impl ParamFieldType {
+    /// Size of a value of this type in bytes. For bitfields, this is the size of the storage
+    /// unit they are packed into.
+    pub fn byte_size(&self) -> usize {
+        match self {
+            Self::s8 { .. } | Self::u8 { .. } => 1,
+            Self::s16 { .. } | Self::u16 { .. } => 2,
+            Self::s32 { .. } | Self::u32 { .. } | Self::b32 | Self::f32 | Self::a32 => 4,
+            Self::f64 => 8,
+            Self::fixstr { length } => *length,
+            Self::fixstrW { length } => length * 2,
+            Self::dummy8 { length: Some(DummyType::Bytes(length)) } => *length,
+            Self::dummy8 { length: None | Some(DummyType::Bits(_)) } => 1,
+        }
+    }
+
+    /// Size in bytes of each value whose bytes are ordered by endianness: the whole type for
+    /// numbers and bitfield storage units, each UTF16 code unit for `fixstrW`, and single bytes for
+    /// `fixstr` and padding.
+    pub(crate) fn endian_unit_size(&self) -> usize {
+        match self {
+            Self::fixstr { .. } | Self::dummy8 { .. } => 1,
+            Self::fixstrW { .. } => 2,
+            _ => self.byte_size(),
+        }
+    }
+
+    /// For bitfields, the kind of storage unit the field is packed into, and its size in bits.
+    ///
+    /// Consecutive bitfields share a storage unit as long as the unit kind is the same and the
+    /// unit has enough bits left. Bit dummies pack into `u8` units.
+    pub(crate) fn bit_storage(&self) -> Option<(ParamFieldTypeKind, u8)> {
+        match self {
+            Self::dummy8 { length: Some(DummyType::Bits(bits)) } => Some((ParamFieldTypeKind::u8, *bits)),
+            Self::s8 { bit_size: Some(bits) } | Self::u8 { bit_size: Some(bits) }
+            | Self::s16 { bit_size: Some(bits) } | Self::u16 { bit_size: Some(bits) }
+            | Self::s32 { bit_size: Some(bits) } | Self::u32 { bit_size: Some(bits) } => Some((self.kind(), *bits)),
+            _ => None,
+        }
+    }
+}
+
+impl ParamFieldDef {
+    /// Size of the field in bytes: the size of its type, times the number of elements for arrays.
+    /// For bitfields, this is the size of the storage unit.
+    pub fn byte_size(&self) -> usize {
+        self.field_type.byte_size() * self.array_len.unwrap_or(1)
+    }
+}
+
+impl ParamDef {
+    /// Size of a single row of this param in bytes
+    pub fn row_size(&self) -> usize {
+        self.field_offsets().last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0)
+    }
+
+    /// Number of bytes in a row reserved by `dummy8` padding fields.
+    ///
+    /// Bit dummies count in whole storage units: a unit counts as padding only when every field
+    /// packed into it is a dummy, and otherwise counts as data.
+    pub fn padding_bytes(&self) -> usize {
+        // Offset and size of each unit, and whether all its fields so far are dummies
+        let mut units: Vec<(usize, usize, bool)> = Vec::new();
+        for (field, offset) in self.fields.iter().zip(self.field_offsets()) {
+            match units.last_mut() {
+                Some((unit_offset, _, padding)) if *unit_offset == offset.byte_offset => *padding &= field.is_dummy(),
+                _ => units.push((offset.byte_offset, offset.byte_size, field.is_dummy())),
+            }
+        }
+        units.into_iter().filter(|(_, _, padding)| *padding).map(|(_, size, _)| size).sum()
+    }
+
+    /// Number of bytes in a row that hold data, which is the [`row_size`](ParamDef::row_size)
+    /// less the [`padding_bytes`](ParamDef::padding_bytes).
+    pub fn data_bytes(&self) -> usize {
+        self.row_size() - self.padding_bytes()
+    }
+
+    /// The location of each field within a row, in declaration order.
+    ///
+    /// Fields are laid out back to back with no alignment. Runs of bitfields are packed into
+    /// storage units from the least significant bit up, starting a new unit whenever the
+    /// unit kind changes, a non-bitfield intervenes, or the current unit is out of bits.
+    pub fn field_offsets(&self) -> Vec<FieldOffset> {
+        let mut offsets = Vec::with_capacity(self.fields.len());
+        let mut offset = 0;
+        // Storage kind, offset of the unit, and bits used so far
+        let mut unit: Option<(ParamFieldTypeKind, usize, u8)> = None;
+
+        for field in &self.fields {
+            let field_type = &field.field_def.field_type;
+            let byte_size = field.field_def.byte_size();
+            match field_type.bit_storage() {
+                Some((kind, bits)) => {
+                    let bit_limit = byte_size * 8;
+                    match &mut unit {
+                        Some((unit_kind, unit_offset, used)) if *unit_kind == kind && *used as usize + bits as usize <= bit_limit => {
+                            offsets.push(FieldOffset {
+                                byte_offset: *unit_offset,
+                                byte_size,
+                                bits: Some(BitPosition { offset: *used, size: bits }),
+                            });
+                            *used += bits;
+                        }
+                        _ => {
+                            unit = Some((kind, offset, bits));
+                            offsets.push(FieldOffset {
+                                byte_offset: offset,
+                                byte_size,
+                                bits: Some(BitPosition { offset: 0, size: bits }),
+                            });
+                            offset += byte_size;
+                        }
+                    }
+                }
+                None => {
+                    unit = None;
+                    offsets.push(FieldOffset { byte_offset: offset, byte_size, bits: None });
+                    offset += byte_size;
+                }
+            }
+        }
+        offsets
+    }
+
+    /// The byte offset within a row of the field with internal name `field_name`, or `None` if
+    /// there is no such field. For bitfields, this is the offset of the storage unit.
+    pub fn offset_of(&self, field_name: &str) -> Option<usize> {
+        self.offset_by_name(field_name).map(|a| a.byte_offset)
+    }
+
+    /// The byte offset within a row of the byte holding the lowest bit of the field with internal
+    /// name `field_name`, and the index of that bit within the byte counting from the least
+    /// significant bit. Non-bitfields start at bit 0 of their first byte.
+    ///
+    /// Bits are numbered as [`ParamDef::read_row`] reads them, so for storage units wider than a
+    /// byte the byte holding a bit depends on the def's endianness.
+    pub fn bit_offset_of(&self, field_name: &str) -> Option<(usize, u8)> {
+        let offset = self.offset_by_name(field_name)?;
+        let Some(bits) = offset.bits else {
+            return Some((offset.byte_offset, 0));
+        };
+        let byte_in_unit = bits.offset as usize / 8;
+        let byte_in_unit = match self.endian {
+            ParamdefEndian::Little => byte_in_unit,
+            ParamdefEndian::Big => offset.byte_size - 1 - byte_in_unit,
+        };
+        Some((offset.byte_offset + byte_in_unit, bits.offset % 8))
+    }
+
+    fn offset_by_name(&self, field_name: &str) -> Option<FieldOffset> {
+        // Unnamed padding can't be looked up
+        let index = self.fields.iter().position(|a| !field_name.is_empty() && a.field_def.name == field_name)?;
+        self.field_offsets().get(index).copied()
+    }
+
+    /// Whether rows of this def can be reinterpreted with `other`: the row size, and in
+    /// declaration order each field's type, bit packing, string or array length and offset,
+    /// all match. Names, defaults and editor metadata are ignored.
+    ///
+    /// This is the exact form of what [`ParamDef::layout_fingerprint`] summarizes as a hash. Unlike
+    /// [`ParamDef::structurally_eq`], the endianness and string format are not compared, as
+    /// they describe how a file is encoded rather than the layout of its rows.
+    pub fn binary_compatible_with(&self, other: &ParamDef) -> bool {
+        self.fields.len() == other.fields.len()
+            && self.fields.iter().zip(&other.fields).all(|(a, b)| {
+                a.field_def.field_type == b.field_def.field_type && a.field_def.array_len == b.field_def.array_len
+            })
+            && self.field_offsets() == other.field_offsets()
+            && self.row_size() == other.row_size()
+    }
+
+    /// Iterates over the fields affected by endianness, with their indices: those stored in units
+    /// wider than a byte. Bitfields are included when their storage unit is.
+    ///
+    /// `fixstr` strings are byte strings and are never included, while `fixstrW` strings are, as
+    /// each UTF16 code unit is stored in the data's endianness. Padding is never included.
+    pub fn multibyte_fields(&self) -> impl Iterator<Item = (usize, &ParamField)> {
+        self.fields.iter().enumerate().filter(|(_, field)| field.field_def.field_type.endian_unit_size() > 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::{BitPosition, FieldOffset, FieldValue};
+    use crate::ParamdefEndian;
+    use crate::test_util::paramdef;
+
+    #[test]
+    fn row_size() {
+        assert_eq!(paramdef(&[]).row_size(), 0);
+        assert_eq!(paramdef(&["s32 a", "f64 b", "u8 c", "fixstrW d[4]", "fixstr e[3]", "dummy8 f[5]", "dummy8 g"]).row_size(), 30);
+    }
+
+    #[test]
+    fn arrays() {
+        let def = paramdef(&["f32 pos[3]", "u8 data[4]", "s16 b"]);
+        assert_eq!(def.fields[0].field_def.byte_size(), 12);
+        assert_eq!(def.field_offsets()[1], FieldOffset { byte_offset: 12, byte_size: 4, bits: None });
+        assert_eq!(def.row_size(), 18);
+    }
+
+    #[test]
+    fn binary_compatible_with() {
+        let def = paramdef(&["u32 a", "u8 b:1", "u8 c:7", "fixstr d[4]"]);
+        let mut renamed = paramdef(&["u32 id", "u8 b:1", "u8 c:7", "fixstr d[4]"]);
+        renamed.fields[0].description = Some("Documented".into());
+        assert!(def.binary_compatible_with(&renamed));
+
+        assert!(!def.binary_compatible_with(&paramdef(&["f32 a", "u8 b:1", "u8 c:7", "fixstr d[4]"])));
+        assert!(!def.binary_compatible_with(&paramdef(&["u32 a", "u16 b:1", "u16 c:7", "fixstr d[4]"])));
+        assert!(!def.binary_compatible_with(&paramdef(&["u32 a", "u8 b:1", "u8 c:7", "fixstr d[8]"])));
+        assert!(!def.binary_compatible_with(&paramdef(&["u32 a", "u8 b:2", "u8 c:6", "fixstr d[4]"])));
+    }
+
+    #[test]
+    fn offset_of() {
+        let def = paramdef(&["s32 refId", "u8 isEnable:1", "u8 isHidden:1", "dummy8[2]", "f32 speed"]);
+        assert_eq!(def.offset_of("refId"), Some(0));
+        assert_eq!(def.offset_of("isHidden"), Some(4));
+        assert_eq!(def.offset_of("speed"), Some(7));
+        assert_eq!(def.offset_of("missing"), None);
+        assert_eq!(def.offset_of(""), None);
+    }
+
+    #[test]
+    fn bit_offset_of() {
+        let mut def = paramdef(&["u32 id", "u8 a:1", "u8 b:1", "u8 c:1", "dummy8 pad:5", "u16 d:4", "u16 e:8", "f32 f"]);
+        let flags: Vec<_> = ["a", "b", "c"].iter().map(|a| def.bit_offset_of(a)).collect();
+        assert_eq!(flags, vec![Some((4, 0)), Some((4, 1)), Some((4, 2))]);
+        assert_eq!(def.bit_offset_of("e"), Some((5, 4)));
+        assert_eq!(def.bit_offset_of("f"), Some((7, 0)));
+        assert_eq!(def.bit_offset_of("id"), Some((0, 0)));
+        assert_eq!(def.bit_offset_of("missing"), None);
+
+        def.endian = ParamdefEndian::Big;
+        assert_eq!(def.bit_offset_of("d"), Some((6, 0)));
+        assert_eq!(def.bit_offset_of("e"), Some((6, 4)));
+        let mut row = vec![0; def.row_size()];
+        row[6] |= 1 << 4;
+        assert_eq!(def.read_row(&row).expect("reads")[6], FieldValue::U16(1));
+    }
+
+    #[test]
+    fn multibyte_fields() {
+        let def = paramdef(&["s8 a", "u8 b:1", "u16 c:3", "fixstr d[8]", "fixstrW e[4]", "dummy8 f[4]", "f32 g[2]", "u8 h[2]", "b32 i"]);
+        let indices: Vec<usize> = def.multibyte_fields().map(|(index, _)| index).collect();
+        assert_eq!(indices, vec![2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn padding_bytes() {
+        let def = paramdef(&["u32 a", "dummy8 b[6]", "dummy8 c:3", "dummy8 d:5", "u8 e:1", "dummy8 f:7", "dummy8 g", "fixstr h[2]"]);
+        assert_eq!(def.row_size(), 15);
+        assert_eq!(def.padding_bytes(), 8);
+        assert_eq!(def.data_bytes(), 7);
+        assert_eq!(paramdef(&[]).padding_bytes(), 0);
+    }
+
+    #[test]
+    fn bitfield_packing() {
+        let def = paramdef(&["u8 a:1", "u8 b:6", "dummy8 c:1", "u8 d:1", "u16 e:4", "u16 f:4", "s16 g:4", "f32 h", "u8 i:1"]);
+        let offsets = def.field_offsets();
+        let bits = |offset, size| Some(BitPosition { offset, size });
+        assert_eq!(offsets, vec![
+            FieldOffset { byte_offset: 0, byte_size: 1, bits: bits(0, 1) },
+            FieldOffset { byte_offset: 0, byte_size: 1, bits: bits(1, 6) },
+            FieldOffset { byte_offset: 0, byte_size: 1, bits: bits(7, 1) },
+            FieldOffset { byte_offset: 1, byte_size: 1, bits: bits(0, 1) },
+            FieldOffset { byte_offset: 2, byte_size: 2, bits: bits(0, 4) },
+            FieldOffset { byte_offset: 2, byte_size: 2, bits: bits(4, 4) },
+            FieldOffset { byte_offset: 4, byte_size: 2, bits: bits(0, 4) },
+            FieldOffset { byte_offset: 6, byte_size: 4, bits: None },
+            FieldOffset { byte_offset: 10, byte_size: 1, bits: bits(0, 1) },
+        ]);
+        assert_eq!(def.row_size(), 11);
+    }
+}
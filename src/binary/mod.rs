@@ -0,0 +1,896 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use crate::{DummyType, ParamDef, ParamdefEndian, ParamField, ParamFieldDef, ParamFieldType, ParamFieldTypeKind};
+
+mod layout;
+
+pub use layout::{BitPosition, FieldOffset};
+
+/// A decoded value of a single field in a row
+#[derive(Clone, PartialEq, Debug)]
+pub enum FieldValue {
+    S8(i8),
+    U8(u8),
+    S16(i16),
+    U16(u16),
+    S32(i32),
+    U32(u32),
+    B32(bool),
+    F32(f32),
+    A32(f32),
+    F64(f64),
+
+    /// The bytes of a ShiftJIS string, up to the first null byte. The text is not decoded.
+    Fixstr(Vec<u8>),
+
+    /// A UTF16 string, up to the first null code unit
+    FixstrW(String),
+
+    /// The raw contents of padding. Bit dummies hold a single byte with the bits' value.
+    Dummy8(Vec<u8>),
+
+    /// The elements of an array of a simple type, such as `f32 pos[3]`
+    Array(Vec<FieldValue>),
+}
+
+impl Display for FieldValue {
+    /// Shows the value in its plain form. Undecoded fixstr bytes are shown lossily as UTF8,
+    /// padding as space separated hex bytes, and arrays as comma separated elements in brackets.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::S8(value) => value.fmt(f),
+            Self::U8(value) => value.fmt(f),
+            Self::S16(value) => value.fmt(f),
+            Self::U16(value) => value.fmt(f),
+            Self::S32(value) => value.fmt(f),
+            Self::U32(value) => value.fmt(f),
+            Self::B32(value) => value.fmt(f),
+            Self::F32(value) | Self::A32(value) => value.fmt(f),
+            Self::F64(value) => value.fmt(f),
+            Self::Fixstr(bytes) => String::from_utf8_lossy(bytes).fmt(f),
+            Self::FixstrW(value) => value.fmt(f),
+            Self::Dummy8(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|a| format!("{:02x}", a)).collect();
+                hex.join(" ").fmt(f)
+            }
+            Self::Array(values) => {
+                let values: Vec<String> = values.iter().map(FieldValue::to_string).collect();
+                format!("[{}]", values.join(", ")).fmt(f)
+            }
+        }
+    }
+}
+
+impl FieldValue {
+    /// The value as a number, or `None` for strings, padding and arrays. `b32` values are 0 or 1.
+    pub fn as_f64(&self) -> Option<f64> {
+        Some(match *self {
+            Self::S8(value) => value as f64,
+            Self::U8(value) => value as f64,
+            Self::S16(value) => value as f64,
+            Self::U16(value) => value as f64,
+            Self::S32(value) => value as f64,
+            Self::U32(value) => value as f64,
+            Self::B32(value) => value as u8 as f64,
+            Self::F32(value) | Self::A32(value) => value as f64,
+            Self::F64(value) => value,
+            Self::Fixstr(_) | Self::FixstrW(_) | Self::Dummy8(_) | Self::Array(_) => return None,
+        })
+    }
+
+    /// The value of an integer field, including bitfields, or `None` for every other type.
+    /// `b32` values are read with [`FieldValue::as_bool`] instead.
+    pub fn as_i64(&self) -> Option<i64> {
+        Some(match *self {
+            Self::S8(value) => value as i64,
+            Self::U8(value) => value as i64,
+            Self::S16(value) => value as i64,
+            Self::U16(value) => value as i64,
+            Self::S32(value) => value as i64,
+            Self::U32(value) => value as i64,
+            _ => return None,
+        })
+    }
+
+    /// Like [`FieldValue::as_i64`], but also `None` for negative values
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_i64().and_then(|a| u64::try_from(a).ok())
+    }
+
+    /// The value of a `b32` field, or of an integer field holding 0 or 1, such as a one bit flag.
+    /// `None` for other values and types.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::B32(value) => Some(*value),
+            value => match value.as_i64()? {
+                0 => Some(false),
+                1 => Some(true),
+                _ => None,
+            },
+        }
+    }
+
+    /// The text of a `fixstrW` field, or of a `fixstr` field whose bytes are valid UTF8, which
+    /// covers plain ASCII. `None` for other types, and for ShiftJIS text, which isn't decoded.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::FixstrW(value) => Some(value),
+            Self::Fixstr(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// An error when reading or writing rows of binary data
+#[derive(Error, Debug)]
+pub enum RowError {
+    #[error("Row data is {actual} bytes long, but {expected} bytes are required")]
+    TooShort {
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Data ends with {remaining} bytes that don't make up a whole row")]
+    TrailingBytes {
+        remaining: usize,
+    },
+
+    #[error("No field named {0}")]
+    UnknownField(String),
+
+    #[error("Value for field {name} doesn't match its type {expected:?}")]
+    MismatchedValue {
+        /// The internal name of the field
+        name: String,
+        expected: ParamFieldTypeKind,
+    },
+
+    #[error("Value for field {name} doesn't fit in the field")]
+    ValueTooLarge {
+        /// The internal name of the field
+        name: String,
+    },
+
+    #[error("Field {name} has a bit size of {bit_size}, which doesn't fit its storage unit")]
+    InvalidBitSize {
+        /// The internal name of the field
+        name: String,
+        bit_size: u8,
+    },
+}
+
+/// Why a value couldn't be written to a field, before the field's name is attached
+enum WriteError {
+    Mismatched,
+    TooLarge,
+}
+
+impl ParamDef {
+    /// Decode a single row from the start of `row`, which must be at least [`ParamDef::row_size`]
+    /// bytes long. Values are returned in declaration order.
+    pub fn read_row(&self, row: &[u8]) -> Result<Vec<FieldValue>, RowError> {
+        let offsets = self.field_offsets();
+        let row_size = offsets.last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0);
+        if row.len() < row_size {
+            return Err(RowError::TooShort { expected: row_size, actual: row.len() });
+        }
+
+        Ok(self.decode_row(&offsets, row))
+    }
+
+    /// Decode only the fields with the given internal names from the start of `row`, which must be
+    /// at least [`ParamDef::row_size`] bytes long. The rest of the row isn't decoded, which saves
+    /// work over [`ParamDef::read_row`] when reading a few fields of wide rows.
+    ///
+    /// Fails with [`RowError::UnknownField`] for the first name that isn't a field of the def.
+    /// When a name is declared more than once, the first field with it is read.
+    pub fn read_fields(&self, row: &[u8], names: &[&str]) -> Result<HashMap<String, FieldValue>, RowError> {
+        let offsets = self.field_offsets();
+        let row_size = offsets.last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0);
+        if row.len() < row_size {
+            return Err(RowError::TooShort { expected: row_size, actual: row.len() });
+        }
+
+        names.iter()
+            .map(|name| {
+                let index = self.fields.iter().position(|a| !name.is_empty() && a.field_def.name == *name)
+                    .ok_or_else(|| RowError::UnknownField((*name).to_owned()))?;
+                Ok(((*name).to_owned(), self.decode_field(&self.fields[index], offsets[index], row)))
+            })
+            .collect()
+    }
+
+    /// Encodes `value` into the field with internal name `field_name` in `row`, which must be at
+    /// least [`ParamDef::row_size`] bytes long, leaving the rest of the row untouched. Bitfields
+    /// are written into their storage unit without changing the other bits in it.
+    ///
+    /// The value must be of the variant [`ParamDef::read_row`] decodes the field as, and must fit:
+    /// bitfield values within their bits, strings within their length, padding of exactly its
+    /// length, and arrays with exactly one element per array slot. Strings shorter than the field
+    /// are padded with nulls. Nothing is written when the value is rejected, or when the field is
+    /// a bitfield whose bit size is zero or doesn't fit its storage unit.
+    pub fn write_field(&self, row: &mut [u8], field_name: &str, value: &FieldValue) -> Result<(), RowError> {
+        let offsets = self.field_offsets();
+        let row_size = offsets.last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0);
+        if row.len() < row_size {
+            return Err(RowError::TooShort { expected: row_size, actual: row.len() });
+        }
+        let index = self.fields.iter().position(|a| !field_name.is_empty() && a.field_def.name == field_name)
+            .ok_or_else(|| RowError::UnknownField(field_name.to_owned()))?;
+        let field_type = &self.fields[index].field_def.field_type;
+        let offset = offsets[index];
+        // Bit sizes set in code aren't checked, and can be zero or wider than the storage unit
+        if let Some(bits) = offset.bits.filter(|a| a.size == 0 || a.offset as usize + a.size as usize > offset.byte_size * 8) {
+            return Err(RowError::InvalidBitSize { name: field_name.to_owned(), bit_size: bits.size });
+        }
+
+        let result = match (self.fields[index].field_def.array_len, value) {
+            (Some(len), FieldValue::Array(elements)) if elements.len() == len => {
+                // Every element is encoded before any is written
+                let element_size = field_type.byte_size();
+                elements.iter()
+                    .map(|a| encode_element(field_type, a, element_size, None, self.endian))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|encoded| row[offset.byte_offset..offset.byte_offset + offset.byte_size].copy_from_slice(&encoded.concat()))
+            }
+            (Some(_), FieldValue::Array(_)) => Err(WriteError::TooLarge),
+            (Some(_), _) | (None, FieldValue::Array(_)) => Err(WriteError::Mismatched),
+            (None, value) => write_element(field_type, value, offset, row, self.endian),
+        };
+        result.map_err(|err| {
+            let name = field_name.to_owned();
+            match err {
+                WriteError::Mismatched => RowError::MismatchedValue { name, expected: field_type.kind() },
+                WriteError::TooLarge => RowError::ValueTooLarge { name },
+            }
+        })
+    }
+
+    /// The number of whole rows that fit in `data_len` bytes, such as the size of a param file's
+    /// data block. A def without fields has rows of 0 bytes, and fits 0 rows.
+    pub fn row_count(&self, data_len: usize) -> usize {
+        data_len.checked_div(self.row_size()).unwrap_or(0)
+    }
+
+    /// Decode `count` rows stored back to back at the start of `data`, as in a param file's
+    /// data block.
+    pub fn read_rows(&self, data: &[u8], count: usize) -> Result<Vec<Vec<FieldValue>>, RowError> {
+        let row_size = self.row_size();
+        let expected = row_size.saturating_mul(count);
+        if data.len() < expected {
+            return Err(RowError::TooShort { expected, actual: data.len() });
+        }
+
+        (0..count)
+            .map(|index| self.read_row(&data[index * row_size..(index + 1) * row_size]))
+            .collect()
+    }
+
+    /// Lazily decode the rows stored back to back in `data`, without decoding them all up front.
+    ///
+    /// Iteration stops after the last whole row. If any bytes remain after it, a final
+    /// [`RowError::TrailingBytes`] is yielded.
+    pub fn rows<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = Result<Vec<FieldValue>, RowError>> + 'a {
+        let offsets = self.field_offsets();
+        let row_size = offsets.last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0);
+        let whole_rows_len = match row_size {
+            0 => 0,
+            row_size => data.len() - data.len() % row_size,
+        };
+        let (whole_rows, trailing) = data.split_at(whole_rows_len);
+
+        whole_rows.chunks(row_size.max(1))
+            .map(move |row| Ok(self.decode_row(&offsets, row)))
+            .chain(Some(trailing.len()).filter(|a| *a > 0).map(|remaining| Err(RowError::TrailingBytes { remaining })))
+    }
+
+    /// A row with every field set to its declared default value, or zero when it has none.
+    ///
+    /// Defaults are clamped to the range of the field's type, including its bit size, as in
+    /// [`ParamFieldDef::default_bytes`]. Strings default to empty and padding to zeroes,
+    /// regardless of any declared default. Every element of an array takes the default.
+    pub fn default_row(&self) -> Vec<FieldValue> {
+        self.fields.iter()
+            .map(|field| {
+                let default = field.field_def.default_value.unwrap_or(0.0);
+                let clamped = match field.field_def.field_type.value_range() {
+                    Some((minimum, maximum)) => default.clamp(minimum, maximum),
+                    None => default,
+                };
+                let value = match &field.field_def.field_type {
+                    ParamFieldType::s8 { .. } => FieldValue::S8(clamped as i8),
+                    ParamFieldType::u8 { .. } => FieldValue::U8(clamped as u8),
+                    ParamFieldType::s16 { .. } => FieldValue::S16(clamped as i16),
+                    ParamFieldType::u16 { .. } => FieldValue::U16(clamped as u16),
+                    ParamFieldType::s32 { .. } => FieldValue::S32(clamped as i32),
+                    ParamFieldType::u32 { .. } => FieldValue::U32(clamped as u32),
+                    ParamFieldType::b32 => FieldValue::B32(default != 0.0),
+                    ParamFieldType::f32 => FieldValue::F32(clamped as f32),
+                    ParamFieldType::a32 => FieldValue::A32(clamped as f32),
+                    ParamFieldType::f64 => FieldValue::F64(default),
+                    ParamFieldType::fixstr { .. } => FieldValue::Fixstr(Vec::new()),
+                    ParamFieldType::fixstrW { .. } => FieldValue::FixstrW(String::new()),
+                    ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => FieldValue::Dummy8(vec![0; *length]),
+                    ParamFieldType::dummy8 { .. } => FieldValue::Dummy8(vec![0]),
+                };
+                match field.field_def.array_len {
+                    Some(len) => FieldValue::Array(vec![value; len]),
+                    None => value,
+                }
+            })
+            .collect()
+    }
+
+    /// Converts a row between big and little endian in place, by reversing the bytes of each
+    /// value affected by endianness. See [`ParamDef::multibyte_fields`].
+    ///
+    /// Numbers are swapped whole, each UTF16 code unit of a `fixstrW` is swapped separately, and
+    /// each element of an array is swapped separately. Bitfields are swapped as their whole
+    /// storage unit. `row` must be exactly [`ParamDef::row_size`] bytes long.
+    pub fn swap_row_endianness(&self, row: &mut [u8]) -> Result<(), RowError> {
+        let offsets = self.field_offsets();
+        let row_size = offsets.last().map(|a| a.byte_offset + a.byte_size).unwrap_or(0);
+        if row.len() < row_size {
+            return Err(RowError::TooShort { expected: row_size, actual: row.len() });
+        }
+        if row.len() > row_size {
+            return Err(RowError::TrailingBytes { remaining: row.len() - row_size });
+        }
+
+        for (field, offset) in self.fields.iter().zip(offsets) {
+            // Later bitfields in a storage unit were swapped along with the first
+            if offset.bits.is_some_and(|a| a.offset > 0) {
+                continue;
+            }
+            let unit_size = field.field_def.field_type.endian_unit_size();
+            if unit_size > 1 {
+                row[offset.byte_offset..offset.byte_offset + offset.byte_size]
+                    .chunks_exact_mut(unit_size)
+                    .for_each(<[u8]>::reverse);
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_row(&self, offsets: &[FieldOffset], row: &[u8]) -> Vec<FieldValue> {
+        self.fields.iter().zip(offsets)
+            .map(|(field, offset)| self.decode_field(field, *offset, row))
+            .collect()
+    }
+
+    fn decode_field(&self, field: &ParamField, offset: FieldOffset, row: &[u8]) -> FieldValue {
+        match field.field_def.array_len {
+            Some(len) => {
+                let element_size = field.field_def.field_type.byte_size();
+                FieldValue::Array((0..len)
+                    .map(|index| FieldOffset {
+                        byte_offset: offset.byte_offset + index * element_size,
+                        byte_size: element_size,
+                        bits: None,
+                    })
+                    .map(|element| read_field(&field.field_def.field_type, row, element, self.endian))
+                    .collect())
+            }
+            None => read_field(&field.field_def.field_type, row, offset, self.endian),
+        }
+    }
+}
+
+impl ParamFieldDef {
+    /// Encodes the declared default value, or zero when there is none, as stored in a row.
+    ///
+    /// Defaults are clamped to the range of the field's type, see
+    /// [`ParamFieldType::value_range`], so `u8 x = 300` encodes 255 and `u8 x:3 = 9` encodes 7.
+    /// Bitfields are encoded unshifted, in the width of their base type. Strings and padding are
+    /// zeroed regardless of any declared default, and arrays repeat the default for each element.
+    pub fn default_bytes(&self, endian: ParamdefEndian) -> Vec<u8> {
+        let default = self.default_value.unwrap_or(0.0);
+        let clamped = match self.field_type.value_range() {
+            Some((minimum, maximum)) => default.clamp(minimum, maximum),
+            None => default,
+        };
+        let size = self.field_type.byte_size();
+        let element = match self.field_type {
+            ParamFieldType::s8 { .. } | ParamFieldType::s16 { .. } | ParamFieldType::s32 { .. } => {
+                write_unsigned(clamped as i64 as u64, size, endian)
+            }
+            ParamFieldType::u8 { .. } | ParamFieldType::u16 { .. } | ParamFieldType::u32 { .. } => {
+                write_unsigned(clamped as u64, size, endian)
+            }
+            ParamFieldType::b32 => write_unsigned((default != 0.0) as u64, size, endian),
+            ParamFieldType::f32 | ParamFieldType::a32 => write_unsigned((clamped as f32).to_bits() as u64, size, endian),
+            ParamFieldType::f64 => write_unsigned(default.to_bits(), size, endian),
+            ParamFieldType::fixstr { .. } | ParamFieldType::fixstrW { .. } | ParamFieldType::dummy8 { .. } => vec![0; size],
+        };
+        element.repeat(self.array_len.unwrap_or(1))
+    }
+}
+
+/// Writes a single value of `field_type` at `offset`, merging bitfields into their storage unit
+fn write_element(field_type: &ParamFieldType, value: &FieldValue, offset: FieldOffset, row: &mut [u8], endian: ParamdefEndian) -> Result<(), WriteError> {
+    let bytes = &mut row[offset.byte_offset..offset.byte_offset + offset.byte_size];
+    let encoded = encode_element(field_type, value, offset.byte_size, offset.bits.map(|a| a.size), endian)?;
+    match offset.bits {
+        Some(bits) => {
+            let mask = bit_mask(bits.size) << bits.offset;
+            let unit = read_unsigned(bytes, endian) & !mask | (read_unsigned(&encoded, endian) << bits.offset) & mask;
+            bytes.copy_from_slice(&write_unsigned(unit, offset.byte_size, endian));
+        }
+        None => bytes.copy_from_slice(&encoded),
+    }
+    Ok(())
+}
+
+/// Encodes a single value of `field_type` into `size` bytes. For bitfields of `bit_size` bits,
+/// the value is checked to fit and encoded unshifted.
+fn encode_element(field_type: &ParamFieldType, value: &FieldValue, size: usize, bit_size: Option<u8>, endian: ParamdefEndian) -> Result<Vec<u8>, WriteError> {
+    let bits = bit_size.unwrap_or((size * 8) as u8);
+    let fits_signed = |value: i64| bits >= 64 || (-(1i64 << (bits - 1))..1i64 << (bits - 1)).contains(&value);
+    let fits_unsigned = |value: u64| value <= bit_mask(bits);
+    let signed = |value: i64| if fits_signed(value) { Ok(write_unsigned(value as u64, size, endian)) } else { Err(WriteError::TooLarge) };
+    let unsigned = |value: u64| if fits_unsigned(value) { Ok(write_unsigned(value, size, endian)) } else { Err(WriteError::TooLarge) };
+
+    match (field_type, value) {
+        (ParamFieldType::s8 { .. }, FieldValue::S8(value)) => signed(*value as i64),
+        (ParamFieldType::s16 { .. }, FieldValue::S16(value)) => signed(*value as i64),
+        (ParamFieldType::s32 { .. }, FieldValue::S32(value)) => signed(*value as i64),
+        (ParamFieldType::u8 { .. }, FieldValue::U8(value)) => unsigned(*value as u64),
+        (ParamFieldType::u16 { .. }, FieldValue::U16(value)) => unsigned(*value as u64),
+        (ParamFieldType::u32 { .. }, FieldValue::U32(value)) => unsigned(*value as u64),
+        (ParamFieldType::b32, FieldValue::B32(value)) => unsigned(*value as u64),
+        (ParamFieldType::f32, FieldValue::F32(value)) | (ParamFieldType::a32, FieldValue::A32(value)) => unsigned(value.to_bits() as u64),
+        (ParamFieldType::f64, FieldValue::F64(value)) => Ok(write_unsigned(value.to_bits(), size, endian)),
+        (ParamFieldType::fixstr { .. }, FieldValue::Fixstr(text)) if text.len() <= size => {
+            let mut bytes = text.clone();
+            bytes.resize(size, 0);
+            Ok(bytes)
+        }
+        (ParamFieldType::fixstrW { .. }, FieldValue::FixstrW(text)) => {
+            let mut bytes: Vec<u8> = text.encode_utf16().flat_map(|a| write_unsigned(a as u64, 2, endian)).collect();
+            if bytes.len() > size {
+                return Err(WriteError::TooLarge);
+            }
+            bytes.resize(size, 0);
+            Ok(bytes)
+        }
+        // Bit dummies hold the bits' value in a single byte, as when read
+        (ParamFieldType::dummy8 { length: Some(DummyType::Bits(_)) }, FieldValue::Dummy8(bytes)) => match bytes.as_slice() {
+            [value] => unsigned(*value as u64),
+            _ => Err(WriteError::TooLarge),
+        },
+        (ParamFieldType::dummy8 { .. }, FieldValue::Dummy8(bytes)) if bytes.len() == size => Ok(bytes.clone()),
+        (ParamFieldType::fixstr { .. }, FieldValue::Fixstr(_)) | (ParamFieldType::dummy8 { .. }, FieldValue::Dummy8(_)) => Err(WriteError::TooLarge),
+        _ => Err(WriteError::Mismatched),
+    }
+}
+
+/// Offset in a param file's header of the byte that is 0xFF for big endian files
+const PARAM_ENDIAN_OFFSET: usize = 0x2C;
+
+/// Offset in a param file's header of the flags describing the header layout
+const PARAM_FORMAT_OFFSET: usize = 0x2D;
+
+/// Flag set when the header holds an offset to the param type rather than the param type itself
+const OFFSET_PARAM_TYPE_FLAG: u8 = 0x80;
+
+/// Reads the param type, such as `EQUIP_PARAM_WEAPON_ST`, from the header of a param file, to
+/// look up its def with [`Paramdex::get_param_def`](crate::Paramdex::get_param_def).
+///
+/// Both header layouts are supported: older games store the param type as a 32 byte string at
+/// offset 0xC, and newer games, which set flag 0x80 in the format byte at 0x2D, store a 64 bit
+/// offset to a null terminated string at 0x10. Returns `None` if `data` is too short to hold a
+/// header, or the param type is empty, unterminated or not ASCII.
+pub fn read_param_type(data: &[u8]) -> Option<String> {
+    if data.len() <= PARAM_FORMAT_OFFSET {
+        return None;
+    }
+    let endian = match data[PARAM_ENDIAN_OFFSET] {
+        0xFF => ParamdefEndian::Big,
+        _ => ParamdefEndian::Little,
+    };
+    let param_type = if data[PARAM_FORMAT_OFFSET] & OFFSET_PARAM_TYPE_FLAG != 0 {
+        let offset = usize::try_from(read_unsigned(&data[0x10..0x18], endian)).ok()?;
+        let string = data.get(offset..)?;
+        &string[..string.iter().position(|a| *a == 0)?]
+    } else {
+        let string = &data[0xC..0x2C];
+        &string[..string.iter().position(|a| *a == 0).unwrap_or(string.len())]
+    };
+    (!param_type.is_empty() && param_type.is_ascii()).then(|| String::from_utf8_lossy(param_type).into_owned())
+}
+
+fn read_field(field_type: &ParamFieldType, row: &[u8], offset: FieldOffset, endian: ParamdefEndian) -> FieldValue {
+    let bytes = &row[offset.byte_offset..offset.byte_offset + offset.byte_size];
+
+    if let Some(bits) = offset.bits {
+        let raw = (read_unsigned(bytes, endian) >> bits.offset) & bit_mask(bits.size);
+        let signed = sign_extend(raw, bits.size);
+        return match field_type {
+            ParamFieldType::s8 { .. } => FieldValue::S8(signed as i8),
+            ParamFieldType::s16 { .. } => FieldValue::S16(signed as i16),
+            ParamFieldType::s32 { .. } => FieldValue::S32(signed as i32),
+            ParamFieldType::u8 { .. } => FieldValue::U8(raw as u8),
+            ParamFieldType::u16 { .. } => FieldValue::U16(raw as u16),
+            ParamFieldType::u32 { .. } => FieldValue::U32(raw as u32),
+            _ => FieldValue::Dummy8(vec![raw as u8]),
+        };
+    }
+
+    match field_type {
+        ParamFieldType::s8 { .. } => FieldValue::S8(bytes[0] as i8),
+        ParamFieldType::u8 { .. } => FieldValue::U8(bytes[0]),
+        ParamFieldType::s16 { .. } => FieldValue::S16(read_unsigned(bytes, endian) as i16),
+        ParamFieldType::u16 { .. } => FieldValue::U16(read_unsigned(bytes, endian) as u16),
+        ParamFieldType::s32 { .. } => FieldValue::S32(read_unsigned(bytes, endian) as i32),
+        ParamFieldType::u32 { .. } => FieldValue::U32(read_unsigned(bytes, endian) as u32),
+        ParamFieldType::b32 => FieldValue::B32(read_unsigned(bytes, endian) != 0),
+        ParamFieldType::f32 => FieldValue::F32(f32::from_bits(read_unsigned(bytes, endian) as u32)),
+        ParamFieldType::a32 => FieldValue::A32(f32::from_bits(read_unsigned(bytes, endian) as u32)),
+        ParamFieldType::f64 => FieldValue::F64(f64::from_bits(read_unsigned(bytes, endian))),
+        ParamFieldType::fixstr { .. } => {
+            FieldValue::Fixstr(bytes.iter().copied().take_while(|a| *a != 0).collect())
+        }
+        ParamFieldType::fixstrW { .. } => {
+            let units: Vec<u16> = bytes.chunks_exact(2)
+                .map(|a| read_unsigned(a, endian) as u16)
+                .take_while(|a| *a != 0)
+                .collect();
+            FieldValue::FixstrW(String::from_utf16_lossy(&units))
+        }
+        ParamFieldType::dummy8 { .. } => {
+            FieldValue::Dummy8(bytes.to_vec())
+        }
+    }
+}
+
+/// Reads an unsigned integer of up to 8 bytes
+fn read_unsigned(bytes: &[u8], endian: ParamdefEndian) -> u64 {
+    let fold = |acc: u64, byte: &u8| (acc << 8) | *byte as u64;
+    match endian {
+        ParamdefEndian::Little => bytes.iter().rev().fold(0, fold),
+        ParamdefEndian::Big => bytes.iter().fold(0, fold),
+    }
+}
+
+/// Writes the lowest `size` bytes of an unsigned integer
+fn write_unsigned(value: u64, size: usize, endian: ParamdefEndian) -> Vec<u8> {
+    let little = &value.to_le_bytes()[..size];
+    match endian {
+        ParamdefEndian::Little => little.to_vec(),
+        ParamdefEndian::Big => little.iter().rev().copied().collect(),
+    }
+}
+
+fn bit_mask(bits: u8) -> u64 {
+    1u64.checked_shl(bits as u32).map(|a| a - 1).unwrap_or(u64::MAX)
+}
+
+/// Sign extends the lowest `bits` bits of `raw`
+fn sign_extend(raw: u64, bits: u8) -> i64 {
+    let shift = 64u32.saturating_sub(bits as u32);
+    ((raw << shift) as i64) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::{read_param_type, FieldValue, RowError};
+    use crate::ParamdefEndian;
+    use crate::test_util::{field, paramdef};
+
+    #[test]
+    fn as_i64() {
+        assert_eq!(FieldValue::S8(-3).as_i64(), Some(-3));
+        assert_eq!(FieldValue::U32(u32::MAX).as_i64(), Some(u32::MAX as i64));
+        assert_eq!(FieldValue::B32(true).as_i64(), None);
+        assert_eq!(FieldValue::F32(1.0).as_i64(), None);
+    }
+
+    #[test]
+    fn as_u64() {
+        assert_eq!(FieldValue::U16(7).as_u64(), Some(7));
+        assert_eq!(FieldValue::S32(5).as_u64(), Some(5));
+        assert_eq!(FieldValue::S32(-1).as_u64(), None);
+        assert_eq!(FieldValue::F64(1.0).as_u64(), None);
+    }
+
+    #[test]
+    fn as_bool() {
+        assert_eq!(FieldValue::B32(true).as_bool(), Some(true));
+        assert_eq!(FieldValue::U8(0).as_bool(), Some(false));
+        assert_eq!(FieldValue::S16(1).as_bool(), Some(true));
+        assert_eq!(FieldValue::U8(2).as_bool(), None);
+        assert_eq!(FieldValue::F32(1.0).as_bool(), None);
+    }
+
+    #[test]
+    fn as_str() {
+        assert_eq!(FieldValue::FixstrW("名前".into()).as_str(), Some("名前"));
+        assert_eq!(FieldValue::Fixstr(b"name".to_vec()).as_str(), Some("name"));
+        assert_eq!(FieldValue::Fixstr(vec![0x96, 0xBC]).as_str(), None);
+        assert_eq!(FieldValue::Dummy8(b"name".to_vec()).as_str(), None);
+    }
+
+    #[test]
+    fn param_type_from_header() {
+        let mut fixed = vec![0; 0x40];
+        fixed[0xC..0x21].copy_from_slice(b"EQUIP_PARAM_WEAPON_ST");
+        assert_eq!(read_param_type(&fixed).as_deref(), Some("EQUIP_PARAM_WEAPON_ST"));
+        fixed[0x2C] = 0xFF;
+        assert_eq!(read_param_type(&fixed).as_deref(), Some("EQUIP_PARAM_WEAPON_ST"));
+
+        let mut offset = vec![0; 0x40];
+        offset[0x2C] = 0xFF;
+        offset[0x2D] = 0x80 | 0x04;
+        offset[0x10..0x18].copy_from_slice(&0x30u64.to_be_bytes());
+        offset[0x30..0x3D].copy_from_slice(b"NPC_PARAM_ST\0");
+        assert_eq!(read_param_type(&offset).as_deref(), Some("NPC_PARAM_ST"));
+        offset[0x3C] = b'X';
+        offset.truncate(0x3D);
+        assert_eq!(read_param_type(&offset), None);
+
+        assert_eq!(read_param_type(&[0; 0x40]), None);
+        assert_eq!(read_param_type(&fixed[..0x20]), None);
+    }
+
+    #[test]
+    fn read_row() {
+        let def = paramdef(&["s32 a", "u16 b", "f32 c", "fixstr d[4]", "fixstrW e[3]", "dummy8 f[2]", "b32 g"]);
+        let mut row = Vec::new();
+        row.extend_from_slice(&(-5i32).to_le_bytes());
+        row.extend_from_slice(&513u16.to_le_bytes());
+        row.extend_from_slice(&1.5f32.to_le_bytes());
+        row.extend_from_slice(b"ab\0\0");
+        row.extend_from_slice(&[b'h', 0, b'i', 0, 0, 0]);
+        row.extend_from_slice(&[1, 2]);
+        row.extend_from_slice(&1u32.to_le_bytes());
+
+        assert_eq!(def.read_row(&row).expect("reads"), vec![
+            FieldValue::S32(-5),
+            FieldValue::U16(513),
+            FieldValue::F32(1.5),
+            FieldValue::Fixstr(b"ab".to_vec()),
+            FieldValue::FixstrW("hi".into()),
+            FieldValue::Dummy8(vec![1, 2]),
+            FieldValue::B32(true),
+        ]);
+    }
+
+    #[test]
+    fn read_arrays() {
+        let def = paramdef(&["f32 pos[2]", "u8 data[3]", "s16 b"]);
+        let mut row = Vec::new();
+        row.extend_from_slice(&1.5f32.to_le_bytes());
+        row.extend_from_slice(&(-2f32).to_le_bytes());
+        row.extend_from_slice(&[1, 2, 3]);
+        row.extend_from_slice(&(-4i16).to_le_bytes());
+
+        let values = def.read_row(&row).expect("reads");
+        assert_eq!(values, vec![
+            FieldValue::Array(vec![FieldValue::F32(1.5), FieldValue::F32(-2.0)]),
+            FieldValue::Array(vec![FieldValue::U8(1), FieldValue::U8(2), FieldValue::U8(3)]),
+            FieldValue::S16(-4),
+        ]);
+        assert_eq!(values[0].to_string(), "[1.5, -2]");
+        assert_eq!(paramdef(&["u16 a[2] = 7"]).default_row(), vec![FieldValue::Array(vec![FieldValue::U16(7); 2])]);
+    }
+
+    #[test]
+    fn swap_row_endianness() {
+        let little = paramdef(&["s16 a", "u8 b:4", "u8 c:4", "u16 d:4", "u16 e:12", "fixstr f[2]", "fixstrW g[2]", "f32 h[2]"]);
+        let mut big = paramdef(&["s16 a", "u8 b:4", "u8 c:4", "u16 d:4", "u16 e:12", "fixstr f[2]", "fixstrW g[2]", "f32 h[2]"]);
+        big.endian = ParamdefEndian::Big;
+
+        let mut row = Vec::new();
+        row.extend_from_slice(&(-2i16).to_le_bytes());
+        row.push(0x21);
+        row.extend_from_slice(&0x4563u16.to_le_bytes());
+        row.extend_from_slice(b"ab");
+        row.extend_from_slice(&[b'h', 0, b'i', 0]);
+        row.extend_from_slice(&1.5f32.to_le_bytes());
+        row.extend_from_slice(&(-3f32).to_le_bytes());
+        let original = row.clone();
+
+        little.swap_row_endianness(&mut row).expect("swaps");
+        assert_eq!(&row[..5], &[0xFF, 0xFE, 0x21, 0x45, 0x63]);
+        assert_eq!(big.read_row(&row).expect("reads"), little.read_row(&original).expect("reads"));
+
+        little.swap_row_endianness(&mut row).expect("swaps");
+        assert_eq!(row, original);
+
+        assert!(matches!(little.swap_row_endianness(&mut [0; 3]), Err(RowError::TooShort { .. })));
+        assert!(matches!(little.swap_row_endianness(&mut [0; 20]), Err(RowError::TrailingBytes { remaining: 1 })));
+    }
+
+    #[test]
+    fn read_fields() {
+        let def = paramdef(&["s32 id", "u8 a:1", "u8 b:7", "f32 pos[2]", "u16 c"]);
+        let mut row = vec![0xFF; 4];
+        row.push(0b0000_0101);
+        row.extend_from_slice(&1.5f32.to_le_bytes());
+        row.extend_from_slice(&(-2.0f32).to_le_bytes());
+        row.extend_from_slice(&7u16.to_le_bytes());
+
+        let values = def.read_fields(&row, &["c", "b", "pos"]).expect("reads");
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["c"], FieldValue::U16(7));
+        assert_eq!(values["b"], FieldValue::U8(2));
+        assert_eq!(values["pos"], FieldValue::Array(vec![FieldValue::F32(1.5), FieldValue::F32(-2.0)]));
+
+        assert!(matches!(def.read_fields(&row, &["id", "missing"]), Err(RowError::UnknownField(name)) if name == "missing"));
+        assert!(matches!(def.read_fields(&row[..10], &["id"]), Err(RowError::TooShort { expected: 15, actual: 10 })));
+    }
+
+    #[test]
+    fn write_field() {
+        let def = paramdef(&["u8 a:1", "u8 b:1", "u8 c:1", "dummy8 pad:5", "s16 d:4", "u16 e:12", "fixstrW name[3]", "f32 pos[2]"]);
+        let mut row = vec![0; def.row_size()];
+        row[0] = 0b1110_0101;
+        def.write_field(&mut row, "b", &FieldValue::U8(1)).expect("writes");
+        assert_eq!(row[0], 0b1110_0111);
+        def.write_field(&mut row, "a", &FieldValue::U8(0)).expect("writes");
+        assert_eq!(row[0], 0b1110_0110);
+
+        def.write_field(&mut row, "d", &FieldValue::S16(-2)).expect("writes");
+        def.write_field(&mut row, "e", &FieldValue::U16(0xABC)).expect("writes");
+        def.write_field(&mut row, "name", &FieldValue::FixstrW("ab".into())).expect("writes");
+        def.write_field(&mut row, "pos", &FieldValue::Array(vec![FieldValue::F32(1.5), FieldValue::F32(-1.0)])).expect("writes");
+        let values = def.read_row(&row).expect("reads");
+        assert_eq!(values[..4], [FieldValue::U8(0), FieldValue::U8(1), FieldValue::U8(1), FieldValue::Dummy8(vec![0b11100])]);
+        assert_eq!(values[4..], [
+            FieldValue::S16(-2),
+            FieldValue::U16(0xABC),
+            FieldValue::FixstrW("ab".into()),
+            FieldValue::Array(vec![FieldValue::F32(1.5), FieldValue::F32(-1.0)]),
+        ]);
+
+        let before = row.clone();
+        assert!(matches!(def.write_field(&mut row, "a", &FieldValue::U8(2)), Err(RowError::ValueTooLarge { .. })));
+        assert!(matches!(def.write_field(&mut row, "d", &FieldValue::S16(8)), Err(RowError::ValueTooLarge { .. })));
+        assert!(matches!(def.write_field(&mut row, "a", &FieldValue::S8(1)), Err(RowError::MismatchedValue { .. })));
+        assert!(matches!(def.write_field(&mut row, "name", &FieldValue::FixstrW("abcd".into())), Err(RowError::ValueTooLarge { .. })));
+        assert!(matches!(def.write_field(&mut row, "pos", &FieldValue::F32(1.0)), Err(RowError::MismatchedValue { .. })));
+        assert!(matches!(def.write_field(&mut row, "pos", &FieldValue::Array(vec![FieldValue::F32(1.0), FieldValue::U8(1)])), Err(RowError::MismatchedValue { .. })));
+        assert!(matches!(def.write_field(&mut row, "missing", &FieldValue::U8(0)), Err(RowError::UnknownField(_))));
+        assert_eq!(row, before);
+    }
+
+    #[test]
+    fn write_field_invalid_bit_size() {
+        for bit_size in [0, 12, 255] {
+            let mut def = paramdef(&["u8 a:1", "u8 b:3", "s8 c:4"]);
+            def.fields[1].field_def.field_type.set_bit_size(bit_size);
+            def.fields[2].field_def.field_type.set_bit_size(bit_size);
+            let mut row = vec![0; def.row_size()];
+            for (name, value) in [("b", FieldValue::U8(0)), ("c", FieldValue::S8(-1))] {
+                match def.write_field(&mut row, name, &value) {
+                    Err(RowError::InvalidBitSize { bit_size: size, .. }) => assert_eq!(size, bit_size),
+                    other => panic!("{}:{} wrote {:?}", name, bit_size, other),
+                }
+            }
+            assert!(row.iter().all(|a| *a == 0));
+        }
+    }
+
+    #[test]
+    fn write_field_big_endian() {
+        let mut def = paramdef(&["u16 a:4", "u16 b:12", "s32 c"]);
+        def.endian = ParamdefEndian::Big;
+        let mut row = vec![0xFF, 0xFF, 0, 0, 0, 0];
+        def.write_field(&mut row, "a", &FieldValue::U16(0)).expect("writes");
+        def.write_field(&mut row, "c", &FieldValue::S32(-2)).expect("writes");
+        assert_eq!(row, [0xFF, 0xF0, 0xFF, 0xFF, 0xFF, 0xFE]);
+    }
+
+    #[test]
+    fn read_row_big_endian() {
+        let mut def = paramdef(&["s16 a", "u32 b"]);
+        def.endian = ParamdefEndian::Big;
+        assert_eq!(
+            def.read_row(&[0xFF, 0xFE, 0, 0, 1, 2]).expect("reads"),
+            vec![FieldValue::S16(-2), FieldValue::U32(258)]
+        );
+    }
+
+    #[test]
+    fn read_bitfields() {
+        let def = paramdef(&["u8 a:1", "u8 b:3", "dummy8 c:4", "u16 d:4", "u16 e:12"]);
+        let row = [0b1010_1011, 0x21, 0x43];
+        assert_eq!(def.read_row(&row).expect("reads"), vec![
+            FieldValue::U8(1),
+            FieldValue::U8(0b101),
+            FieldValue::Dummy8(vec![0b1010]),
+            FieldValue::U16(0x1),
+            FieldValue::U16(0x432),
+        ]);
+    }
+
+    #[test]
+    fn read_signed_bitfields() {
+        let def = paramdef(&["s16 temperature:12", "s16 other:4"]);
+        let raw: u16 = (0b0111 << 12) | (-100i16 as u16 & 0xFFF);
+        assert_eq!(
+            def.read_row(&raw.to_le_bytes()).expect("reads"),
+            vec![FieldValue::S16(-100), FieldValue::S16(7)]
+        );
+    }
+
+    #[test]
+    fn read_rows() {
+        let def = paramdef(&["u8 a", "s8 b"]);
+        assert_eq!(def.read_rows(&[1, 255, 2, 254, 3], 2).expect("reads"), vec![
+            vec![FieldValue::U8(1), FieldValue::S8(-1)],
+            vec![FieldValue::U8(2), FieldValue::S8(-2)],
+        ]);
+        assert!(matches!(
+            def.read_rows(&[1, 255, 2], 2),
+            Err(RowError::TooShort { expected: 4, actual: 3 })
+        ));
+    }
+
+    #[test]
+    fn row_count() {
+        let def = paramdef(&["u32 a", "u16 b"]);
+        assert_eq!(def.row_count(0), 0);
+        assert_eq!(def.row_count(12), 2);
+        assert_eq!(def.row_count(17), 2);
+        assert_eq!(paramdef(&[]).row_count(100), 0);
+    }
+
+    #[test]
+    fn rows() {
+        let def = paramdef(&["u8 a", "s8 b"]);
+        let rows: Vec<_> = def.rows(&[1, 255, 2, 254]).map(|a| a.expect("reads")).collect();
+        assert_eq!(rows, vec![
+            vec![FieldValue::U8(1), FieldValue::S8(-1)],
+            vec![FieldValue::U8(2), FieldValue::S8(-2)],
+        ]);
+
+        let mut rows = def.rows(&[1, 255, 2]);
+        assert!(rows.next().expect("row").is_ok());
+        assert!(matches!(rows.next(), Some(Err(RowError::TrailingBytes { remaining: 1 }))));
+        assert!(rows.next().is_none());
+    }
+
+    #[test]
+    fn default_bytes() {
+        let bytes = |def: &str, endian| field(def).field_def.default_bytes(endian);
+        assert_eq!(bytes("u32 x = 100", ParamdefEndian::Little), vec![100, 0, 0, 0]);
+        assert_eq!(bytes("u32 x = 100", ParamdefEndian::Big), vec![0, 0, 0, 100]);
+        assert_eq!(bytes("s16 x = -2", ParamdefEndian::Little), vec![0xFE, 0xFF]);
+        assert_eq!(bytes("s8 x = -200", ParamdefEndian::Little), vec![0x80]);
+        assert_eq!(bytes("u8 x = 300", ParamdefEndian::Little), vec![255]);
+        assert_eq!(bytes("u16 x = -1", ParamdefEndian::Little), vec![0, 0]);
+        assert_eq!(bytes("u8 x:3 = 9", ParamdefEndian::Little), vec![7]);
+        assert_eq!(bytes("b32 x = 1", ParamdefEndian::Big), vec![0, 0, 0, 1]);
+        assert_eq!(bytes("f32 x = 1.5", ParamdefEndian::Little), 1.5f32.to_le_bytes().to_vec());
+        assert_eq!(bytes("f64 x = -0.25", ParamdefEndian::Big), (-0.25f64).to_be_bytes().to_vec());
+        assert_eq!(bytes("u16 x", ParamdefEndian::Little), vec![0, 0]);
+        assert_eq!(bytes("fixstr name[8]", ParamdefEndian::Little), vec![0; 8]);
+        assert_eq!(bytes("fixstrW name[4]", ParamdefEndian::Little), vec![0; 8]);
+        assert_eq!(bytes("dummy8 pad[3] = -1", ParamdefEndian::Little), vec![0; 3]);
+        assert_eq!(bytes("u16 x[2] = 258", ParamdefEndian::Big), vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn default_row() {
+        let def = paramdef(&["s32 a = -1", "u8 b:1 = 1", "f32 c = 0.5", "b32 d = 1", "fixstr e[4]", "dummy8 f[3] = -1", "u16 g"]);
+        assert_eq!(def.default_row(), vec![
+            FieldValue::S32(-1),
+            FieldValue::U8(1),
+            FieldValue::F32(0.5),
+            FieldValue::B32(true),
+            FieldValue::Fixstr(Vec::new()),
+            FieldValue::Dummy8(vec![0, 0, 0]),
+            FieldValue::U16(0),
+        ]);
+
+        // Defaults too large for a bitfield are clamped to its bit size, so they can be written back
+        let def = paramdef(&["u8 a:3 = 9", "s8 b:4 = -20", "u8 c = 300"]);
+        let row = def.default_row();
+        assert_eq!(row, vec![FieldValue::U8(7), FieldValue::S8(-8), FieldValue::U8(255)]);
+        let mut bytes = vec![0; def.row_size()];
+        def.write_field(&mut bytes, "a", &row[0]).expect("writes");
+        def.write_field(&mut bytes, "b", &row[1]).expect("writes");
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use thiserror::Error;
+use crate::display_format::DisplayFormat;
+use crate::{DummyType, EditFlags, EnumRef, Paramdex, ParamDef, ParamdefEndian, ParamdefFormat, ParamField, ParamFieldDef, ParamFieldType};
+
+/// Identifies a cache, at the start of every cache
+const MAGIC: &[u8; 4] = b"PDXC";
+
+/// Version of the cache format, bumped whenever the layout of a cache changes, or what is
+/// cached from a def
+const FORMAT_VERSION: u32 = 5;
+
+/// An error when loading a cached [Paramdex]
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("Reading or writing cache failed: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Input is not a Paramdex cache")]
+    NotACache,
+
+    #[error("Cache format version {found} is not supported, expected {expected}")]
+    UnsupportedVersion {
+        found: u32,
+        expected: u32,
+    },
+
+    #[error("Cache is corrupt: {0}")]
+    Corrupt(&'static str),
+}
+
+impl Paramdex {
+    /// Writes the Paramdex to a compact binary cache, to be reloaded with [`Paramdex::load_cache`]
+    /// much faster than parsing XML.
+    ///
+    /// The cache starts with a format version, and caches written by a version of this crate with
+    /// a different format are rejected when loaded. Defs are written in sorted order of param
+    /// type, so the same Paramdex always produces the same bytes.
+    pub fn save_cache<W: Write>(&self, writer: W) -> Result<(), CacheError> {
+        let mut writer = CacheWriter(writer);
+        writer.0.write_all(MAGIC)?;
+        writer.u32(FORMAT_VERSION)?;
+
+        let mut param_types: Vec<&String> = self.definitions.keys().collect();
+        param_types.sort_unstable();
+        writer.len(param_types.len())?;
+        for param_type in param_types {
+            writer.string(param_type)?;
+            writer.paramdef(&self.definitions[param_type])?;
+        }
+        Ok(())
+    }
+
+    /// Loads a Paramdex from a cache written by [`Paramdex::save_cache`].
+    pub fn load_cache<R: Read>(reader: R) -> Result<Paramdex, CacheError> {
+        let mut reader = CacheReader(reader);
+        let mut magic = [0; 4];
+        reader.0.read_exact(&mut magic).map_err(|_| CacheError::NotACache)?;
+        if &magic != MAGIC {
+            return Err(CacheError::NotACache);
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(CacheError::UnsupportedVersion { found: version, expected: FORMAT_VERSION });
+        }
+
+        let mut paramdex = Paramdex::empty();
+        for _ in 0..reader.len()? {
+            let param_type = reader.string()?;
+            paramdex.definitions.insert(param_type, reader.paramdef()?);
+        }
+        Ok(paramdex)
+    }
+}
+
+struct CacheWriter<W>(W);
+
+impl<W: Write> CacheWriter<W> {
+    fn u8(&mut self, value: u8) -> io::Result<()> {
+        self.0.write_all(&[value])
+    }
+
+    fn u32(&mut self, value: u32) -> io::Result<()> {
+        self.0.write_all(&value.to_le_bytes())
+    }
+
+    fn len(&mut self, value: usize) -> io::Result<()> {
+        self.0.write_all(&(value as u64).to_le_bytes())
+    }
+
+    fn f64(&mut self, value: f64) -> io::Result<()> {
+        self.0.write_all(&value.to_le_bytes())
+    }
+
+    fn bool(&mut self, value: bool) -> io::Result<()> {
+        self.u8(value as u8)
+    }
+
+    fn string(&mut self, value: &str) -> io::Result<()> {
+        self.len(value.len())?;
+        self.0.write_all(value.as_bytes())
+    }
+
+    /// Writes a presence flag, followed by the value if present
+    fn option<T>(&mut self, value: Option<&T>, write: impl FnOnce(&mut Self, &T) -> io::Result<()>) -> io::Result<()> {
+        self.bool(value.is_some())?;
+        value.map_or(Ok(()), |value| write(self, value))
+    }
+
+    fn paramdef(&mut self, paramdef: &ParamDef) -> io::Result<()> {
+        self.string(&paramdef.param_type)?;
+        self.u32(paramdef.data_version)?;
+        self.bool(paramdef.endian == ParamdefEndian::Big)?;
+        self.bool(paramdef.string_format == ParamdefFormat::UTF16)?;
+        self.u32(paramdef.format_version)?;
+        self.len(paramdef.fields.len())?;
+        paramdef.fields.iter().try_for_each(|a| self.field(a))
+    }
+
+    fn field(&mut self, field: &ParamField) -> io::Result<()> {
+        self.field_type(&field.field_def.field_type)?;
+        self.string(&field.field_def.name)?;
+        self.option(field.field_def.default_value.as_ref(), |w, a| w.f64(*a))?;
+        self.option(field.field_def.array_len.as_ref(), |w, a| w.len(*a))?;
+        self.option(field.field_def.comment.as_ref(), |w, a| w.string(a))?;
+        self.option(field.display_name.as_ref(), |w, a| w.string(a))?;
+        self.option(field.enum_tdf.as_ref(), |w, a| w.string(a.name()))?;
+        self.option(field.description.as_ref(), |w, a| w.string(a))?;
+        self.string_map(&field.descriptions)?;
+        self.option(field.printf_format.as_ref(), |w, a| w.string(a))?;
+        self.option(field.display_format.as_ref(), |w, a| w.display_format(a))?;
+        self.option(field.edit_flags.as_ref(), |w, a| {
+            w.bool(a.wrap)?;
+            w.bool(a.lock)
+        })?;
+        self.option(field.minimum.as_ref(), |w, a| w.f64(*a))?;
+        self.option(field.maximum.as_ref(), |w, a| w.f64(*a))?;
+        self.option(field.increment.as_ref(), |w, a| w.f64(*a))?;
+        self.option(field.sort_id.as_ref(), |w, a| w.len(*a))?;
+        self.string_map(&field.raw)
+    }
+
+    /// Writes the entries of a map in sorted order, so the same map always produces the same bytes
+    fn string_map(&mut self, map: &HashMap<String, String>) -> io::Result<()> {
+        let mut entries: Vec<(&String, &String)> = map.iter().collect();
+        entries.sort_unstable();
+        self.len(entries.len())?;
+        for (key, value) in entries {
+            self.string(key)?;
+            self.string(value)?;
+        }
+        Ok(())
+    }
+
+    fn field_type(&mut self, field_type: &ParamFieldType) -> io::Result<()> {
+        // Bit sizes are written as 0 when absent, as a bitfield can't be 0 bits wide
+        let (tag, payload) = match field_type {
+            ParamFieldType::s8 { bit_size } => (0, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::u8 { bit_size } => (1, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::s16 { bit_size } => (2, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::u16 { bit_size } => (3, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::s32 { bit_size } => (4, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::u32 { bit_size } => (5, bit_size.unwrap_or(0) as usize),
+            ParamFieldType::b32 => (6, 0),
+            ParamFieldType::f32 => (7, 0),
+            ParamFieldType::a32 => (8, 0),
+            ParamFieldType::f64 => (9, 0),
+            ParamFieldType::fixstr { length } => (10, *length),
+            ParamFieldType::fixstrW { length } => (11, *length),
+            ParamFieldType::dummy8 { length: None } => (12, 0),
+            ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => (13, *length),
+            ParamFieldType::dummy8 { length: Some(DummyType::Bits(bits)) } => (14, *bits as usize),
+        };
+        self.u8(tag)?;
+        self.len(payload)
+    }
+
+    fn display_format(&mut self, display_format: &DisplayFormat) -> io::Result<()> {
+        self.string(&display_format.prefix)?;
+        self.string(&display_format.flags)?;
+        self.option(display_format.width.as_ref(), |w, a| w.len(*a))?;
+        self.option(display_format.precision.as_ref(), |w, a| w.len(*a))?;
+        self.u32(display_format.conversion as u32)?;
+        self.string(&display_format.suffix)
+    }
+}
+
+struct CacheReader<R>(R);
+
+impl<R: Read> CacheReader<R> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], CacheError> {
+        let mut bytes = [0; N];
+        self.0.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, CacheError> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheError> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn len(&mut self) -> Result<usize, CacheError> {
+        usize::try_from(u64::from_le_bytes(self.bytes()?)).map_err(|_| CacheError::Corrupt("length out of range"))
+    }
+
+    fn f64(&mut self) -> Result<f64, CacheError> {
+        Ok(f64::from_le_bytes(self.bytes()?))
+    }
+
+    fn bool(&mut self) -> Result<bool, CacheError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CacheError::Corrupt("invalid boolean")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, CacheError> {
+        let len = self.len()?;
+        let mut bytes = Vec::new();
+        // Read through take, so a corrupt length fails at the end of input instead of allocating
+        (&mut self.0).take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        String::from_utf8(bytes).map_err(|_| CacheError::Corrupt("invalid UTF-8 in string"))
+    }
+
+    fn option<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T, CacheError>) -> Result<Option<T>, CacheError> {
+        match self.bool()? {
+            true => read(self).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    fn paramdef(&mut self) -> Result<ParamDef, CacheError> {
+        let param_type = self.string()?;
+        let data_version = self.u32()?;
+        let endian = ParamdefEndian::from(self.bool()?);
+        let string_format = ParamdefFormat::from(self.bool()?);
+        let format_version = self.u32()?;
+        let fields = (0..self.len()?).map(|_| self.field()).collect::<Result<_, _>>()?;
+        Ok(ParamDef { param_type, data_version, endian, string_format, format_version, fields })
+    }
+
+    fn field(&mut self) -> Result<ParamField, CacheError> {
+        Ok(ParamField {
+            field_def: ParamFieldDef {
+                field_type: self.field_type()?,
+                name: self.string()?,
+                default_value: self.option(Self::f64)?,
+                array_len: self.option(Self::len)?,
+                comment: self.option(Self::string)?,
+            },
+            display_name: self.option(Self::string)?,
+            enum_tdf: self.option(Self::string)?.map(EnumRef::from),
+            description: self.option(Self::string)?,
+            descriptions: self.string_map()?,
+            printf_format: self.option(Self::string)?,
+            display_format: self.option(Self::display_format)?,
+            edit_flags: self.option(|r| Ok(EditFlags { wrap: r.bool()?, lock: r.bool()? }))?,
+            minimum: self.option(Self::f64)?,
+            maximum: self.option(Self::f64)?,
+            increment: self.option(Self::f64)?,
+            sort_id: self.option(Self::len)?,
+            raw: self.string_map()?,
+        })
+    }
+
+    fn string_map(&mut self) -> Result<HashMap<String, String>, CacheError> {
+        (0..self.len()?).map(|_| Ok((self.string()?, self.string()?))).collect()
+    }
+
+    fn field_type(&mut self) -> Result<ParamFieldType, CacheError> {
+        let tag = self.u8()?;
+        let payload = self.len()?;
+        let bit_size = match payload {
+            0 => Ok(None),
+            bits => u8::try_from(bits).map(Some).map_err(|_| CacheError::Corrupt("bit size out of range")),
+        };
+        Ok(match tag {
+            0 => ParamFieldType::s8 { bit_size: bit_size? },
+            1 => ParamFieldType::u8 { bit_size: bit_size? },
+            2 => ParamFieldType::s16 { bit_size: bit_size? },
+            3 => ParamFieldType::u16 { bit_size: bit_size? },
+            4 => ParamFieldType::s32 { bit_size: bit_size? },
+            5 => ParamFieldType::u32 { bit_size: bit_size? },
+            6 => ParamFieldType::b32,
+            7 => ParamFieldType::f32,
+            8 => ParamFieldType::a32,
+            9 => ParamFieldType::f64,
+            10 => ParamFieldType::fixstr { length: payload },
+            11 => ParamFieldType::fixstrW { length: payload },
+            12 => ParamFieldType::dummy8 { length: None },
+            13 => ParamFieldType::dummy8 { length: Some(DummyType::Bytes(payload)) },
+            14 => ParamFieldType::dummy8 { length: bit_size?.map(DummyType::Bits) },
+            _ => return Err(CacheError::Corrupt("unknown field type")),
+        })
+    }
+
+    fn display_format(&mut self) -> Result<DisplayFormat, CacheError> {
+        Ok(DisplayFormat {
+            prefix: self.string()?,
+            flags: self.string()?,
+            width: self.option(Self::len)?,
+            precision: self.option(Self::len)?,
+            conversion: char::from_u32(self.u32()?).ok_or(CacheError::Corrupt("invalid conversion"))?,
+            suffix: self.string()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::cache::{CacheError, FORMAT_VERSION};
+    use crate::display_format::DisplayFormat;
+    use crate::test_util::paramdef;
+    use crate::{EditFlags, Paramdex};
+
+    fn paramdex() -> Paramdex {
+        let mut def = paramdef(&["s32 id = -1 // reference", "u8 a:1", "dummy8 b:7", "fixstrW name[16]", "dummy8 pad[3]", "dummy8 c"]);
+        let field = &mut def.fields[0];
+        field.display_name = Some("ID".into());
+        field.enum_tdf = Some("ON_OFF".into());
+        field.description = Some("Multi\nline".into());
+        field.descriptions.insert("JP".into(), "説明".into());
+        field.descriptions.insert("EN".into(), "Description".into());
+        field.raw.insert("@Def".into(), "s32 id = -1 // reference".into());
+        field.printf_format = Some("%d%%".into());
+        field.display_format = DisplayFormat::from_str("%d%%").ok();
+        field.edit_flags = Some(EditFlags { wrap: true, lock: false });
+        (field.minimum, field.maximum, field.increment, field.sort_id) = (Some(-1.0), Some(f64::MAX), Some(0.5), Some(100));
+
+        let mut paramdex = Paramdex::empty();
+        paramdex.insert(def);
+        let mut other = paramdef(&["f64 a", "a32 b", "b32 c", "u16 d:3", "fixstr e[4]", "f32 f[3]"]);
+        other.param_type = "OTHER_PARAM_ST".into();
+        paramdex.insert(other);
+        paramdex
+    }
+
+    #[test]
+    fn round_trip() {
+        let paramdex = paramdex();
+        let mut cache = Vec::new();
+        paramdex.save_cache(&mut cache).expect("saves");
+        let loaded = Paramdex::load_cache(cache.as_slice()).expect("loads");
+        for param_type in ["TEST_PARAM_ST", "OTHER_PARAM_ST"] {
+            assert_eq!(loaded.get_param_def(param_type), paramdex.get_param_def(param_type));
+        }
+    }
+
+    #[test]
+    fn rejects_stale_and_corrupt_caches() {
+        let mut cache = Vec::new();
+        paramdex().save_cache(&mut cache).expect("saves");
+
+        let mut stale = cache.clone();
+        stale[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(Paramdex::load_cache(stale.as_slice()), Err(CacheError::UnsupportedVersion { .. })));
+        assert!(matches!(Paramdex::load_cache(&b"<PARAMDEF>"[..]), Err(CacheError::NotACache)));
+        assert!(matches!(Paramdex::load_cache(&cache[..cache.len() - 1]), Err(CacheError::Io(_))));
+    }
+}
@@ -0,0 +1,527 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use crate::binary::FieldOffset;
+use crate::{DummyType, ParamDef, ParamdefEndian, ParamField, ParamFieldType};
+
+/// Rust keywords and reserved words that must be written as raw identifiers when used as field
+/// names
+const RUST_KEYWORDS: [&str; 48] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen",
+    "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Rust keywords that can't be raw identifiers, and `_`, which get an underscore appended
+const RUST_NON_RAW_KEYWORDS: [&str; 5] = ["crate", "self", "super", "Self", "_"];
+
+/// C keywords that can't be used as field names, and get an underscore appended
+const C_KEYWORDS: [&str; 37] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum", "extern",
+    "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return", "short", "signed",
+    "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void", "volatile", "while", "_Bool",
+    "_Complex", "_Imaginary",
+];
+
+/// Options for generating source code from a [ParamDef]
+#[derive(Clone, Debug)]
+pub struct CodegenOptions {
+    /// Name of the generated struct. Defaults to the param type.
+    pub struct_name: Option<String>,
+
+    /// Whether to emit field descriptions as doc comments
+    pub doc_comments: bool,
+
+    /// Traits to derive on the generated struct, such as `Clone` or `Debug`
+    pub derives: Vec<String>,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions {
+            struct_name: None,
+            doc_comments: true,
+            derives: Vec::new(),
+        }
+    }
+}
+
+/// A member of a generated struct: either a single field, or a storage unit of bitfields. Fields
+/// are paired with their sanitized names.
+enum Member<'a> {
+    Field(&'a ParamField, &'a str, FieldOffset),
+    BitGroup(Vec<(&'a ParamField, &'a str, FieldOffset)>),
+}
+
+impl Member<'_> {
+    fn offset(&self) -> FieldOffset {
+        match self {
+            Member::Field(_, _, offset) => *offset,
+            Member::BitGroup(fields) => fields[0].2,
+        }
+    }
+
+    /// Alignment of the member's type in C and Rust
+    fn alignment(&self) -> usize {
+        match self {
+            Member::Field(field, _, _) => match field.field_def.field_type {
+                ParamFieldType::fixstr { .. } | ParamFieldType::dummy8 { .. } => 1,
+                ParamFieldType::fixstrW { .. } => 2,
+                ref field_type => field_type.byte_size(),
+            },
+            Member::BitGroup(_) => self.offset().byte_size,
+        }
+    }
+}
+
+/// Groups fields into struct members, collecting bitfields that share a storage unit. `names`
+/// are the names of the fields, based on [`ParamDef::sanitized_field_names`].
+fn members<'a>(paramdef: &'a ParamDef, names: &'a [String]) -> Vec<Member<'a>> {
+    let mut members: Vec<Member> = Vec::new();
+    for ((field, name), offset) in paramdef.fields.iter().zip(names).zip(paramdef.field_offsets()) {
+        match (offset.bits, members.last_mut()) {
+            (Some(bits), Some(Member::BitGroup(group))) if bits.offset > 0 && group[0].2.byte_offset == offset.byte_offset => {
+                group.push((field, name, offset));
+            }
+            (Some(_), _) => members.push(Member::BitGroup(vec![(field, name, offset)])),
+            (None, _) => members.push(Member::Field(field, name, offset)),
+        }
+    }
+    members
+}
+
+/// Whether every member is naturally aligned, so a C layout needs no packing to match the row
+fn naturally_aligned(members: &[Member], row_size: usize) -> bool {
+    let max_alignment = members.iter().map(Member::alignment).max().unwrap_or(1);
+    members.iter().all(|a| a.offset().byte_offset.is_multiple_of(a.alignment())) && row_size.is_multiple_of(max_alignment)
+}
+
+impl ParamDef {
+    /// Generates a Rust struct with the same layout as a row of this param.
+    ///
+    /// Fields use their sanitized names, see [`ParamDef::sanitized_field_names`], with
+    /// descriptions as doc comments. Strings and padding
+    /// become arrays, and `b32` becomes a `u32`. The struct is `#[repr(C)]`, and also `packed`
+    /// when any field isn't naturally aligned, so its size always matches [`ParamDef::row_size`].
+    ///
+    /// Rust has no bitfields, so each storage unit of bitfields becomes a single unsigned integer
+    /// named `bits_` followed by its byte offset, documented with the fields it holds and their
+    /// bit positions from the least significant bit.
+    ///
+    /// Keywords are written as raw identifiers, except those that can't be, such as `self`,
+    /// which get `_` appended. Should that or a `bits_` name collide with another field, it gets
+    /// `_` and a number appended as in [`ParamDef::sanitized_field_names`].
+    pub fn to_rust_struct(&self, opts: CodegenOptions) -> String {
+        let names = self.sanitized_field_names();
+        let members = members(self, &names);
+        let member_names = rust_member_names(&members);
+        let name = opts.struct_name.as_deref().unwrap_or(&self.param_type);
+        let mut out = String::new();
+
+        writeln!(out, "/// Row of `{}`, data version {}", self.param_type, self.data_version).unwrap();
+        writeln!(out, "#[allow(non_camel_case_types, non_snake_case)]").unwrap();
+        if !opts.derives.is_empty() {
+            writeln!(out, "#[derive({})]", opts.derives.join(", ")).unwrap();
+        }
+        match naturally_aligned(&members, self.row_size()) {
+            true => writeln!(out, "#[repr(C)]").unwrap(),
+            false => writeln!(out, "#[repr(C, packed)]").unwrap(),
+        }
+        writeln!(out, "pub struct {} {{", name).unwrap();
+
+        for (member, member_name) in members.iter().zip(&member_names) {
+            match member {
+                Member::Field(field, _, _) => {
+                    if opts.doc_comments {
+                        write_doc(&mut out, field.description.as_deref().unwrap_or(""));
+                    }
+                    let field_type = match field.field_def.array_len {
+                        Some(len) => format!("[{}; {}]", rust_type(&field.field_def.field_type), len),
+                        None => rust_type(&field.field_def.field_type),
+                    };
+                    writeln!(out, "    pub {}: {},", member_name, field_type).unwrap();
+                }
+                Member::BitGroup(fields) => {
+                    let offset = member.offset();
+                    if opts.doc_comments {
+                        write_doc(&mut out, &bit_group_doc(fields));
+                    }
+                    writeln!(out, "    pub {}: u{},", member_name, offset.byte_size * 8).unwrap();
+                }
+            }
+        }
+
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+impl ParamDef {
+    /// Generates a C struct with the same layout as a row of this param, using the fixed width
+    /// types from `<stdint.h>`.
+    ///
+    /// Fields use their sanitized names, see [`ParamDef::sanitized_field_names`]. Strings and
+    /// padding become arrays, bitfields become bitfield members of their base type, and
+    /// descriptions become comments. When any field isn't naturally aligned, the struct is
+    /// wrapped in `#pragma pack(push, 1)`. The endianness of the data is noted in a comment, as C
+    /// can't express it.
+    ///
+    /// Each storage unit of bitfields is filled out with an unnamed bitfield, so the layout is
+    /// the same whichever way a compiler packs partially used units.
+    ///
+    /// Keywords get `_` appended. Should that collide with another field, it gets `_` and a
+    /// number appended as in [`ParamDef::sanitized_field_names`].
+    pub fn to_c_struct(&self) -> String {
+        let names = c_field_names(&self.sanitized_field_names());
+        let members = members(self, &names);
+        let packed = !naturally_aligned(&members, self.row_size());
+        let endian = match self.endian {
+            ParamdefEndian::Little => "little",
+            ParamdefEndian::Big => "big",
+        };
+        let mut out = String::new();
+
+        writeln!(out, "/* Row of {}, data version {}. Values are {} endian. */", self.param_type, self.data_version, endian).unwrap();
+        if packed {
+            writeln!(out, "#pragma pack(push, 1)").unwrap();
+        }
+        writeln!(out, "struct {} {{", self.param_type).unwrap();
+
+        for member in &members {
+            match member {
+                Member::Field(field, name, _) => {
+                    write_c_comment(&mut out, field.description.as_deref().unwrap_or(""));
+                    let line = match &field.field_def.field_type {
+                        ParamFieldType::fixstr { length } => format!("char {}[{}];", name, length),
+                        ParamFieldType::fixstrW { length } => format!("uint16_t {}[{}];", name, length),
+                        ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => format!("uint8_t {}[{}];", name, length),
+                        field_type => match field.field_def.array_len {
+                            Some(len) => format!("{} {}[{}];", c_type(field_type), name, len),
+                            None => format!("{} {};", c_type(field_type), name),
+                        },
+                    };
+                    writeln!(out, "    {}", line).unwrap();
+                }
+                Member::BitGroup(fields) => {
+                    let mut used = 0;
+                    for (field, name, offset) in fields {
+                        let bits = offset.bits.expect("bitfield");
+                        write_c_comment(&mut out, field.description.as_deref().unwrap_or(""));
+                        let field_type = c_type(&field.field_def.field_type);
+                        writeln!(out, "    {} {} : {};", field_type, name, bits.size).unwrap();
+                        used = bits.offset as usize + bits.size as usize;
+                    }
+                    let unit_bits = member.offset().byte_size * 8;
+                    if used < unit_bits {
+                        writeln!(out, "    uint{}_t : {};", unit_bits, unit_bits - used).unwrap();
+                    }
+                }
+            }
+        }
+
+        writeln!(out, "}};").unwrap();
+        if packed {
+            writeln!(out, "#pragma pack(pop)").unwrap();
+        }
+        out
+    }
+}
+
+impl ParamField {
+    /// The internal name transformed into a valid C and Rust identifier. Full width ASCII, such
+    /// as `ｇ`, is narrowed to ASCII, and any other character that can't appear in an identifier
+    /// becomes `_`. Names that don't start with a letter or `_` get a leading `_`, and unnamed
+    /// padding is named `unnamed`.
+    ///
+    /// Keywords are left as is, as each language escapes them differently.
+    pub fn sanitized_name(&self) -> String {
+        if self.field_def.name.is_empty() {
+            return String::from("unnamed");
+        }
+        let mut sanitized: String = self.field_def.name.chars()
+            .map(|a| match a {
+                '\u{FF01}'..='\u{FF5E}' => char::from_u32(a as u32 - 0xFEE0).expect("ASCII"),
+                a => a,
+            })
+            .map(|a| if a.is_ascii_alphanumeric() { a } else { '_' })
+            .collect();
+        if !sanitized.starts_with(|a: char| a.is_ascii_alphabetic() || a == '_') {
+            sanitized.insert(0, '_');
+        }
+        sanitized
+    }
+}
+
+impl ParamDef {
+    /// The sanitized names of every field in declaration order, see
+    /// [`ParamField::sanitized_name`]. Names are unique: a name already taken by an earlier
+    /// field gets `_` and a number appended, counting up from 1 until it is unique.
+    pub fn sanitized_field_names(&self) -> Vec<String> {
+        let mut used: HashSet<String> = HashSet::new();
+        self.fields.iter()
+            .map(|field| unique_name(field.sanitized_name(), &mut used))
+            .collect()
+    }
+}
+
+/// Returns `base`, or `base` with `_` and the lowest number from 1 appended that makes it
+/// unique, and marks the name as used
+fn unique_name(base: String, used: &mut HashSet<String>) -> String {
+    let name = match used.contains(&base) {
+        true => (1..).map(|a| format!("{}_{}", base, a)).find(|a| !used.contains(a)).expect("unique name"),
+        false => base,
+    };
+    used.insert(name.clone());
+    name
+}
+
+fn c_type(field_type: &ParamFieldType) -> &'static str {
+    match field_type {
+        ParamFieldType::s8 { .. } => "int8_t",
+        ParamFieldType::s16 { .. } => "int16_t",
+        ParamFieldType::u16 { .. } => "uint16_t",
+        ParamFieldType::s32 { .. } => "int32_t",
+        ParamFieldType::u32 { .. } | ParamFieldType::b32 => "uint32_t",
+        ParamFieldType::f32 | ParamFieldType::a32 => "float",
+        ParamFieldType::f64 => "double",
+        ParamFieldType::u8 { .. } | ParamFieldType::fixstr { .. } | ParamFieldType::fixstrW { .. }
+        | ParamFieldType::dummy8 { .. } => "uint8_t",
+    }
+}
+
+/// The C identifier of each field, from the sanitized field names. Keywords get `_` appended,
+/// made unique against every other field.
+fn c_field_names(names: &[String]) -> Vec<String> {
+    let mut used: HashSet<String> = names.iter().cloned().collect();
+    names.iter()
+        .map(|name| match C_KEYWORDS.contains(&name.as_str()) {
+            true => unique_name(format!("{}_", name), &mut used),
+            false => name.clone(),
+        })
+        .collect()
+}
+
+/// Writes a comment for a struct member, without trailing whitespace
+fn write_c_comment(out: &mut String, comment: &str) {
+    for line in comment.lines().map(str::trim_end).filter(|a| !a.is_empty()) {
+        writeln!(out, "    // {}", line).unwrap();
+    }
+}
+
+fn rust_type(field_type: &ParamFieldType) -> String {
+    match field_type {
+        ParamFieldType::s8 { .. } => "i8".into(),
+        ParamFieldType::u8 { .. } => "u8".into(),
+        ParamFieldType::s16 { .. } => "i16".into(),
+        ParamFieldType::u16 { .. } => "u16".into(),
+        ParamFieldType::s32 { .. } => "i32".into(),
+        ParamFieldType::u32 { .. } | ParamFieldType::b32 => "u32".into(),
+        ParamFieldType::f32 | ParamFieldType::a32 => "f32".into(),
+        ParamFieldType::f64 => "f64".into(),
+        ParamFieldType::fixstr { length } => format!("[u8; {}]", length),
+        ParamFieldType::fixstrW { length } => format!("[u16; {}]", length),
+        ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => format!("[u8; {}]", length),
+        ParamFieldType::dummy8 { .. } => "[u8; 1]".into(),
+    }
+}
+
+/// The Rust identifier of each member. Fields keep their sanitized names, escaped when they are
+/// keywords, and the names given to keywords that can't be raw and to bitfield storage units
+/// are made unique against every other member.
+fn rust_member_names(members: &[Member]) -> Vec<String> {
+    let mut used: HashSet<String> = members.iter()
+        .filter_map(|a| match a {
+            Member::Field(_, name, _) => Some(name.to_string()),
+            Member::BitGroup(_) => None,
+        })
+        .collect();
+    members.iter()
+        .map(|member| match member {
+            Member::Field(_, name, _) if RUST_NON_RAW_KEYWORDS.contains(name) => unique_name(format!("{}_", name), &mut used),
+            Member::Field(_, name, _) if RUST_KEYWORDS.contains(name) => format!("r#{}", name),
+            Member::Field(_, name, _) => name.to_string(),
+            Member::BitGroup(_) => unique_name(format!("bits_{}", member.offset().byte_offset), &mut used),
+        })
+        .collect()
+}
+
+fn bit_group_doc(fields: &[(&ParamField, &str, FieldOffset)]) -> String {
+    let mut doc = String::from("Bitfields, from the least significant bit:");
+    for (field, _, offset) in fields {
+        let bits = offset.bits.expect("bitfield");
+        write!(doc, "\n- `{}`: {} bit(s) at bit {}", field.field_def.name, bits.size, bits.offset).unwrap();
+    }
+    doc
+}
+
+/// Writes a doc comment for a struct member, without trailing whitespace
+fn write_doc(out: &mut String, doc: &str) {
+    for line in doc.lines().map(str::trim_end) {
+        match line.is_empty() {
+            true => writeln!(out, "    ///").unwrap(),
+            false => writeln!(out, "    /// {}", line).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::CodegenOptions;
+    use crate::test_util::paramdef;
+
+    /// Computes the size of a generated C struct, following C layout rules with every bitfield
+    /// storage unit filled
+    fn c_struct_size(source: &str) -> usize {
+        let packed = source.contains("#pragma pack(push, 1)");
+        let (mut size, mut max_alignment, mut unit_bits) = (0usize, 1, 0);
+        let members = source.lines()
+            .map(str::trim)
+            .filter(|a| a.ends_with(';') && !a.starts_with("//") && !a.starts_with('}'));
+        for member in members {
+            let type_size = match member.split(' ').next().expect("type") {
+                "char" | "int8_t" | "uint8_t" => 1,
+                "int16_t" | "uint16_t" => 2,
+                "int32_t" | "uint32_t" | "float" => 4,
+                "double" => 8,
+                other => panic!("Unknown type {}", other),
+            };
+            let alignment = if packed { 1 } else { type_size };
+            max_alignment = max_alignment.max(alignment);
+            if let Some((_, bits)) = member.split_once(" : ") {
+                if unit_bits == 0 {
+                    size = size.next_multiple_of(alignment) + type_size;
+                }
+                unit_bits = (unit_bits + bits.trim_end_matches(';').parse::<usize>().expect("bits")) % (type_size * 8);
+                continue;
+            }
+            unit_bits = 0;
+            let count = member.split_once('[').map(|(_, a)| a.trim_end_matches("];").parse().expect("length")).unwrap_or(1);
+            size = size.next_multiple_of(alignment) + type_size * count;
+        }
+        size.next_multiple_of(max_alignment)
+    }
+
+    #[test]
+    fn c_struct() {
+        let mut def = paramdef(&["s32 refId", "u8 a:1", "s8 b:3", "u8 default", "fixstr tex[1]", "fixstrW name[2]"]);
+        def.fields[0].description = Some("Referenced ID".into());
+        assert_eq!(def.to_c_struct(), "\
+/* Row of TEST_PARAM_ST, data version 1. Values are little endian. */
+struct TEST_PARAM_ST {
+    // Referenced ID
+    int32_t refId;
+    uint8_t a : 1;
+    uint8_t : 7;
+    int8_t b : 3;
+    uint8_t : 5;
+    uint8_t default_;
+    char tex[1];
+    uint16_t name[2];
+};
+");
+        assert_eq!(c_struct_size(&def.to_c_struct()), def.row_size());
+    }
+
+    #[test]
+    fn sanitized_field_names() {
+        let mut def = paramdef(&["u32 ｇradFactor", "u32 gradFactor", "u32 a", "u32 b", "u32 c", "u32 type"]);
+        def.fields[2].field_def.name = "2nd sfx".into();
+        def.fields[3].field_def.name = "a-b".into();
+        def.fields[4].field_def.name = "a_b".into();
+        assert_eq!(def.fields[0].sanitized_name(), "gradFactor");
+        assert_eq!(def.sanitized_field_names(), vec!["gradFactor", "gradFactor_1", "_2nd_sfx", "a_b", "a_b_1", "type"]);
+
+        let rust = def.to_rust_struct(CodegenOptions::default());
+        assert!(rust.contains("    pub gradFactor_1: u32,\n    pub _2nd_sfx: u32,"), "{}", rust);
+        assert!(rust.contains("    pub r#type: u32,"), "{}", rust);
+        assert!(def.to_c_struct().contains("    uint32_t a_b_1;\n"));
+
+        let unnamed = paramdef(&["dummy8[2]", "u8 a", "dummy8[3]"]);
+        assert_eq!(unnamed.sanitized_field_names(), vec!["unnamed", "a", "unnamed_1"]);
+    }
+
+    #[test]
+    fn c_struct_size_matches_row_size() {
+        let defs = [
+            paramdef(&["u8 a", "f32 b", "u16 c:3", "u16 d:13", "u32 e:1", "f64 f"]),
+            paramdef(&["f64 a", "u8 b:4", "u8 c:4", "u16 d", "fixstrW e[1]", "dummy8 f[2]", "dummy8 g:2", "s32 h"]),
+            paramdef(&["u32 a:31", "u32 b:2", "s16 c", "dummy8 d[6]"]),
+            paramdef(&["f32 pos[3]", "u8 data[4]", "s16 e[2]"]),
+            paramdef(&["u8 a", "f32 pos[3]"]),
+        ];
+        for def in defs {
+            assert_eq!(c_struct_size(&def.to_c_struct()), def.row_size(), "{}", def.to_c_struct());
+        }
+    }
+
+    #[test]
+    fn rust_struct() {
+        let mut def = paramdef(&["s32 refId", "u8 a:1", "u8 b:7", "u8 type", "fixstrW name[3]", "dummy8 pad[4]"]);
+        def.fields[0].description = Some("Referenced ID\n\nOr -1".into());
+        let opts = CodegenOptions { derives: vec!["Clone".into(), "Copy".into()], ..Default::default() };
+        assert_eq!(def.to_rust_struct(opts), "\
+/// Row of `TEST_PARAM_ST`, data version 1
+#[allow(non_camel_case_types, non_snake_case)]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct TEST_PARAM_ST {
+    /// Referenced ID
+    ///
+    /// Or -1
+    pub refId: i32,
+    /// Bitfields, from the least significant bit:
+    /// - `a`: 1 bit(s) at bit 0
+    /// - `b`: 7 bit(s) at bit 1
+    pub bits_4: u8,
+    pub r#type: u8,
+    pub name: [u16; 3],
+    pub pad: [u8; 4],
+}
+");
+    }
+
+    #[test]
+    fn rust_struct_keywords() {
+        let mut def = paramdef(&["u32 self", "u32 crate", "u32 yield", "u32 Self", "u32 x", "u8 a:1", "u32 bits_16", "u32 self_"]);
+        def.fields[4].field_def.name = "-".into();
+        let opts = CodegenOptions { doc_comments: false, ..Default::default() };
+        assert_eq!(def.to_rust_struct(opts), "\
+/// Row of `TEST_PARAM_ST`, data version 1
+#[allow(non_camel_case_types, non_snake_case)]
+#[repr(C, packed)]
+pub struct TEST_PARAM_ST {
+    pub self__1: u32,
+    pub crate_: u32,
+    pub r#yield: u32,
+    pub Self_: u32,
+    pub __: u32,
+    pub bits_20: u8,
+    pub bits_16: u32,
+    pub self_: u32,
+}
+");
+    }
+
+    #[test]
+    fn c_struct_keywords() {
+        let def = paramdef(&["u8 default", "u8 default_", "u8 int:1", "u8 int_1:1"]);
+        let c = def.to_c_struct();
+        assert!(c.contains("    uint8_t default__1;\n    uint8_t default_;\n"), "{}", c);
+        assert!(c.contains("    uint8_t int_ : 1;\n    uint8_t int_1 : 1;\n"), "{}", c);
+    }
+
+    #[test]
+    fn rust_struct_packed() {
+        let def = paramdef(&["u8 a", "f32 b[2]"]);
+        let opts = CodegenOptions { struct_name: Some("Row".into()), doc_comments: false, ..Default::default() };
+        assert_eq!(def.to_rust_struct(opts), "\
+/// Row of `TEST_PARAM_ST`, data version 1
+#[allow(non_camel_case_types, non_snake_case)]
+#[repr(C, packed)]
+pub struct Row {
+    pub a: u8,
+    pub b: [f32; 2],
+}
+");
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use pest_derive::Parser;
 
@@ -8,12 +9,13 @@ struct DefParser;
 use pest::iterators::{Pair, Pairs};
 use pest::{Parser, Span};
 use pest::error::ErrorVariant;
-use crate::{DummyType, ParamFieldDef, ParamFieldType};
+use crate::{BitSizeError, DummyType, ParamFieldDef, ParamFieldType};
 
-fn tokenize(input: &str) -> Result<Pairs<Rule>, DefParseError> {
-    DefParser::parse(Rule::def, input.as_ref()).map_err(|a| a.into())
+fn tokenize(input: &str, rule: Rule) -> Result<Pairs<'_, Rule>, DefParseError> {
+    DefParser::parse(rule, input).map_err(|a| a.into())
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct ErrSpan {
     pub start: usize,
@@ -24,32 +26,58 @@ pub struct ErrSpan {
 pub enum DefParseError {
     #[error("Failed to parse def line: {0}")]
     ParseError(#[from] pest::error::Error<Rule>),
+
+    #[error("Unrecognized field type: {0}")]
+    UnrecognizedType(String),
 }
 
 use thiserror::Error;
 
+/// Options for parsing def strings.
+///
+/// The default options match parsing with [`ParamFieldDef::from_str`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DefParseOptions {
+    /// Whether to skip unrecognized text after a def rather than failing, such as annotations
+    /// added by forks of Paramdex. The recognized suffixes are an array or string length `[n]`,
+    /// a bit size `:n`, a default value `= value` or `:= value`, and a trailing `// comment`.
+    /// A comment after the skipped text is still read.
+    pub ignore_unknown_suffixes: bool,
+}
+
 pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDef, DefParseError> {
-    let tokenized = tokenize(input_str.as_ref())?.next().expect("First pair");
-    assert_eq!(tokenized.as_rule(), Rule::def, "Rule is not def");
-    let inner = tokenized.into_inner().next().expect("def type pair");
+    parse_param_field_def_with_options(input_str, &DefParseOptions::default())
+}
+
+/// Parses a def string, such as `u8 isEnable:1 = 1`, using the given options.
+pub fn parse_param_field_def_with_options<S: AsRef<str>>(input_str: S, opts: &DefParseOptions) -> Result<ParamFieldDef, DefParseError> {
+    parse_with_skipped(input_str.as_ref(), opts).map(|(field_def, _)| field_def)
+}
+
+/// Parses a def string, also returning the unrecognized text that was skipped, if any
+pub(crate) fn parse_with_skipped(input_str: &str, opts: &DefParseOptions) -> Result<(ParamFieldDef, Option<String>), DefParseError> {
+    let rule = if opts.ignore_unknown_suffixes { Rule::def_lenient } else { Rule::def };
+    let tokenized = tokenize(input_str, rule)?.next().expect("First pair");
+    assert_eq!(tokenized.as_rule(), rule, "Rule is not def");
+    let mut pairs = tokenized.into_inner().peekable();
+    let mut field_def = parse_def_pair(pairs.next().expect("def type pair"))?;
+    let skipped = pairs.next_if(|a| a.as_rule() == Rule::unknown_suffix)
+        .map(|a| a.as_str().trim().to_owned())
+        .filter(|a| !a.is_empty());
+    field_def.comment = pairs.next()
+        .filter(|a| a.as_rule() == Rule::def_comment)
+        .map(|a| a.into_inner().next().expect("comment text").as_str().trim().to_owned());
+    Ok((field_def, skipped))
+}
+
+/// Parses the def itself, without a trailing comment
+fn parse_def_pair(inner: Pair<Rule>) -> Result<ParamFieldDef, DefParseError> {
     match inner.as_rule() {
         Rule::def_simple => {
             let mut inner = inner.into_inner();
             let simple_field_type = inner.next().expect("getting simple field type");
 
-            let field_type = match simple_field_type.as_str() {
-                "s8" => ParamFieldType::s8,
-                "u8" => ParamFieldType::u8 { bit_size: None },
-                "s16" => ParamFieldType::s16,
-                "u16" => ParamFieldType::u16 { bit_size: None },
-                "s32" => ParamFieldType::s32,
-                "u32" => ParamFieldType::u32 { bit_size: None },
-                "f32" => ParamFieldType::f32,
-                "f64" => ParamFieldType::f64,
-                "a32" | "angle32" => ParamFieldType::a32,
-                "b32" => ParamFieldType::b32,
-                _ => unreachable!()
-            };
+            let field_type = parse_simple_field_type(simple_field_type.as_str()).expect("simple field type");
 
             let field_name = get_field_name(&mut inner);
 
@@ -57,6 +85,8 @@ pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDe
                 field_type,
                 name: field_name,
                 default_value: None,
+                array_len: None,
+                comment: None,
             };
 
             for suffix in inner {
@@ -64,19 +94,19 @@ pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDe
                     Rule::suffix_bitsize => {
                         let span = suffix.as_span();
                         let number = suffix.into_inner().next().expect("number");
-                        let bit_size = u8::from_str(number.as_str()).expect("number_str to u8");
-                        if compiled_field_def.field_type.supports_bit_size() {
-                            compiled_field_def.field_type.set_bit_size(bit_size);
-                        } else {
-                            return Err(
-                                pest::error::Error::new_from_span(
-                                    ErrorVariant::CustomError {
-                                        message: String::from("Bit size not supported on this type")
-                                    },
-                                    span,
-                                ).into());
+                        // Sizes too large for a u8 are out of range for every type
+                        let bit_size = u8::from_str(number.as_str()).unwrap_or(u8::MAX);
+                        if let Err(err) = compiled_field_def.field_type.try_set_bit_size(bit_size) {
+                            let message = match err {
+                                BitSizeError::Unsupported(_) => String::from("Bit size not supported on this type"),
+                                BitSizeError::OutOfRange { max, .. } => format!("Bit size must be between 1 and {}", max),
+                            };
+                            return Err(custom_error(message, span));
                         }
                     }
+                    Rule::suffix_array => {
+                        compiled_field_def.array_len.replace(get_array_size(suffix)?);
+                    }
                     Rule::def_default_suffix => {
                         let default_val = get_default(suffix);
                         compiled_field_def.default_value.replace(default_val);
@@ -89,19 +119,23 @@ pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDe
         Rule::def_dummy => {
             let mut inner = inner.into_inner();
             assert_eq!(inner.next().expect("after dummy").as_rule(), Rule::dummy_field_type);
-            let field_name = get_field_name(&mut inner);
+            // Some older defs leave padding unnamed, such as `dummy8[4]`
+            let field_name = match inner.peek().map(|a| a.as_rule()) {
+                Some(Rule::field_name) => get_field_name(&mut inner),
+                _ => String::new(),
+            };
             let mut dummy_length: Option<DummyType> = None;
             let mut default: Option<f64> = None;
             for suffix in inner {
                 match suffix.as_rule() {
                     Rule::suffix_array => {
                         dummy_length.replace(
-                            DummyType::Bytes(get_array_size(suffix))
+                            DummyType::Bytes(get_array_size(suffix)?)
                         );
                     }
                     Rule::suffix_bitsize => {
                         dummy_length.replace(
-                            DummyType::Bits(get_bitsize(suffix))
+                            DummyType::Bits(get_dummy_bitsize(suffix)?)
                         );
                     }
                     Rule::def_default_suffix => {
@@ -114,6 +148,8 @@ pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDe
                 name: field_name,
                 default_value: default,
                 field_type: ParamFieldType::dummy8 { length: dummy_length },
+                array_len: None,
+                comment: None,
             })
         },
         Rule::def_fixstr => {
@@ -121,43 +157,134 @@ pub fn parse_param_field_def<S: AsRef<str>>(input_str: S) -> Result<ParamFieldDe
             let fixstr_type = inner.next().expect("fixstr_type");
             let name = inner.next().expect("field_name").as_str().into();
             let suffix_array = inner.next().expect("suffix_array");
-            let array_len = get_array_size(suffix_array);
+            let array_len = get_array_size(suffix_array)?;
             let field_type = match fixstr_type.as_str() {
                 "fixstr" => ParamFieldType::fixstr { length: array_len },
                 "fixstrW" => ParamFieldType::fixstrW { length: array_len },
                 _ => unreachable!()
             };
-            Ok(ParamFieldDef { name, field_type, default_value: None })
+            Ok(ParamFieldDef { name, field_type, default_value: None, array_len: None, comment: None })
         }
         Rule::def_unrecog => {
             let inner = inner.into_inner().next().expect("field type");
-            Err(DefParseError::ParseError(pest::error::Error::new_from_span(ErrorVariant::CustomError { message: "Unrecognized type".into() },inner.as_span())))
+            Err(DefParseError::UnrecognizedType(inner.as_str().into()))
         }
         _ => unreachable!()
     }
 }
 
+/// Parses the type token of a def without a size or length suffix, such as `u32`
+fn parse_simple_field_type(token: &str) -> Option<ParamFieldType> {
+    Some(match token {
+        "s8" => ParamFieldType::s8 { bit_size: None },
+        "u8" => ParamFieldType::u8 { bit_size: None },
+        "s16" => ParamFieldType::s16 { bit_size: None },
+        "u16" => ParamFieldType::u16 { bit_size: None },
+        "s32" => ParamFieldType::s32 { bit_size: None },
+        "u32" => ParamFieldType::u32 { bit_size: None },
+        "f32" => ParamFieldType::f32,
+        "f64" => ParamFieldType::f64,
+        "a32" | "angle32" => ParamFieldType::a32,
+        "b32" => ParamFieldType::b32,
+        _ => return None,
+    })
+}
+
+/// Parses a bare type token, as accepted in a def, with default payloads. String lengths are 0
+/// as they are not part of the token.
+pub fn parse_field_type(token: &str) -> Result<ParamFieldType, DefParseError> {
+    match token {
+        "fixstr" => Ok(ParamFieldType::fixstr { length: 0 }),
+        "fixstrW" => Ok(ParamFieldType::fixstrW { length: 0 }),
+        "dummy8" => Ok(ParamFieldType::dummy8 { length: None }),
+        token => parse_simple_field_type(token).ok_or_else(|| DefParseError::UnrecognizedType(token.into())),
+    }
+}
+
+impl ParamFieldType {
+    /// The shortest token for this type in a def string, such as `a32` for `angle32`
+    pub(crate) fn token(&self) -> &'static str {
+        match self {
+            Self::s8 { .. } => "s8",
+            Self::u8 { .. } => "u8",
+            Self::s16 { .. } => "s16",
+            Self::u16 { .. } => "u16",
+            Self::s32 { .. } => "s32",
+            Self::u32 { .. } => "u32",
+            Self::b32 => "b32",
+            Self::f32 => "f32",
+            Self::a32 => "a32",
+            Self::f64 => "f64",
+            Self::fixstr { .. } => "fixstr",
+            Self::fixstrW { .. } => "fixstrW",
+            Self::dummy8 { .. } => "dummy8",
+        }
+    }
+}
+
+impl Display for ParamFieldDef {
+    /// Writes the def string this def parses from, such as `u8 isEnable:1 = 1`. The type is
+    /// written as its shortest token, so `angle32` is written as `a32`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.field_type.token())?;
+        if !self.name.is_empty() {
+            write!(f, " {}", self.name)?;
+        }
+        match self.field_type {
+            ParamFieldType::s8 { bit_size: Some(bits) } | ParamFieldType::u8 { bit_size: Some(bits) }
+            | ParamFieldType::s16 { bit_size: Some(bits) } | ParamFieldType::u16 { bit_size: Some(bits) }
+            | ParamFieldType::s32 { bit_size: Some(bits) } | ParamFieldType::u32 { bit_size: Some(bits) }
+            | ParamFieldType::dummy8 { length: Some(DummyType::Bits(bits)) } => write!(f, ":{}", bits)?,
+            ParamFieldType::fixstr { length } | ParamFieldType::fixstrW { length }
+            | ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => write!(f, "[{}]", length)?,
+            _ => {}
+        }
+        if let Some(array_len) = self.array_len {
+            write!(f, "[{}]", array_len)?;
+        }
+        // Floats display without exponents, which is what the grammar accepts
+        if let Some(default) = self.default_value {
+            write!(f, " = {}", default)?;
+        }
+        if let Some(comment) = &self.comment {
+            write!(f, " // {}", comment)?;
+        }
+        Ok(())
+    }
+}
+
 fn get_default(inner: Pair<Rule>) -> f64 {
     assert_eq!(inner.as_rule(), Rule::def_default_suffix, "Rule is not default");
     let default_inner = inner.into_inner().next().expect("default inner");
     f64::from_str(default_inner.as_str()).expect("default str to f64")
 }
 
-fn parse_pair<T: FromStr>(inner: Pair<Rule>) -> T where <T as FromStr>::Err: std::fmt::Debug {
-    let text = inner.as_str();
-    T::from_str(text).expect("parse invalid")
-}
-
-fn get_array_size(inner: Pair<Rule>) -> usize {
+/// Gets an array or string length, which must be at least 1
+fn get_array_size(inner: Pair<Rule>) -> Result<usize, DefParseError> {
     assert_eq!(inner.as_rule(), Rule::suffix_array, "Rule is not suffix_array");
+    let span = inner.as_span();
     let num = inner.into_inner().next().expect("getting number");
-    parse_pair(num)
+    match usize::from_str(num.as_str()) {
+        Ok(0) => Err(custom_error(String::from("Length must be at least 1"), span)),
+        Ok(len) => Ok(len),
+        Err(_) => Err(custom_error(String::from("Length is too large"), span)),
+    }
 }
 
-fn get_bitsize(inner: Pair<Rule>) -> u8 {
+/// Gets the bit size of a dummy8, which must fit within its single byte
+fn get_dummy_bitsize(inner: Pair<Rule>) -> Result<u8, DefParseError> {
     assert_eq!(inner.as_rule(), Rule::suffix_bitsize, "Rule is not suffix_bitsize");
+    let span = inner.as_span();
     let num = inner.into_inner().next().expect("getting number");
-    parse_pair(num)
+    match u8::from_str(num.as_str()) {
+        Ok(bits @ 1..=8) => Ok(bits),
+        _ => Err(custom_error(String::from("Bit size must be between 1 and 8"), span)),
+    }
+}
+
+/// An error for a def that matches the grammar but has an invalid value at `span`
+fn custom_error(message: String, span: Span) -> DefParseError {
+    pest::error::Error::new_from_span(ErrorVariant::CustomError { message }, span).into()
 }
 
 fn get_field_name(inner: &mut Pairs<Rule>) -> String {
@@ -178,14 +305,58 @@ mod tests {
     use std::fs::{File};
     use std::io::{BufReader, Read};
     use crate::{DummyType, ParamFieldDef, ParamFieldType};
-    use crate::deserialize::field_def_parse::parse_param_field_def;
+    use crate::deserialize::field_def_parse::{parse_param_field_def, parse_param_field_def_with_options, parse_with_skipped, DefParseError, DefParseOptions};
 
-    impl PartialEq for ParamFieldDef {
-        fn eq(&self, other: &Self) -> bool {
-            self.field_type == other.field_type && self.default_value == other.default_value && self.name == other.name
+    #[test]
+    fn field_type_tokens() {
+        let tokens = [
+            ("s8", ParamFieldType::s8 { bit_size: None }),
+            ("u8", ParamFieldType::u8 { bit_size: None }),
+            ("s16", ParamFieldType::s16 { bit_size: None }),
+            ("u16", ParamFieldType::u16 { bit_size: None }),
+            ("s32", ParamFieldType::s32 { bit_size: None }),
+            ("u32", ParamFieldType::u32 { bit_size: None }),
+            ("b32", ParamFieldType::b32),
+            ("f32", ParamFieldType::f32),
+            ("a32", ParamFieldType::a32),
+            ("angle32", ParamFieldType::a32),
+            ("f64", ParamFieldType::f64),
+            ("fixstr", ParamFieldType::fixstr { length: 0 }),
+            ("fixstrW", ParamFieldType::fixstrW { length: 0 }),
+            ("dummy8", ParamFieldType::dummy8 { length: None }),
+        ];
+        for (token, field_type) in tokens {
+            assert_eq!(ParamFieldType::try_from(token).expect("parses"), field_type);
         }
     }
 
+    #[test]
+    #[should_panic]
+    fn field_type_token_unknown() {
+        ParamFieldType::try_from("u64").expect("parses");
+    }
+
+    #[test]
+    fn unrecognized_type() {
+        match parse_param_field_def("u64 value") {
+            Err(DefParseError::UnrecognizedType(field_type)) => assert_eq!(field_type, "u64"),
+            other => panic!("Expected unrecognized type, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn trailing_comments() {
+        let parsed = parse_param_field_def("u32 x // tweak later").expect("parses");
+        assert_eq!(parsed.name, "x");
+        assert_eq!(parsed.comment.as_deref(), Some("tweak later"));
+
+        let parsed = parse_param_field_def("s16 y:4 = -1//signed").expect("parses");
+        assert_eq!((parsed.default_value, parsed.comment.as_deref()), (Some(-1.0), Some("signed")));
+        assert_eq!(parse_param_field_def("fixstr z[8] // name").expect("parses").comment.as_deref(), Some("name"));
+        assert_eq!(parse_param_field_def("dummy8 pad[2]").expect("parses").comment, None);
+        assert!(parse_param_field_def("u32 x tweak later").is_err());
+    }
+
     #[test]
     fn dummy_parse_array() {
         let def = "dummy8 reserve_last[32]";
@@ -195,6 +366,8 @@ mod tests {
                 name: "reserve_last".to_string(),
                 default_value: None,
                 field_type: ParamFieldType::dummy8 { length: Some(DummyType::Bytes(32)) },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -208,10 +381,91 @@ mod tests {
                 name: "disableParamReserve1".to_string(),
                 default_value: None,
                 field_type: ParamFieldType::dummy8 { length: Some(DummyType::Bits(7)) },
+                array_len: None,
+                comment: None,
             }
         )
     }
 
+    #[test]
+    fn simple_array() {
+        let def = "f32 pos[3] = 1";
+        assert_eq!(
+            parse_param_field_def(def).expect("parses"),
+            ParamFieldDef {
+                name: "pos".to_string(),
+                default_value: Some(1.0),
+                field_type: ParamFieldType::f32,
+                array_len: Some(3),
+                comment: None,
+            }
+        );
+        assert!(parse_param_field_def("u8 data[4]:2").is_err());
+    }
+
+    #[test]
+    fn array_length_out_of_range() {
+        for (def, message) in [
+            ("u8 x[0]", "Length must be at least 1"),
+            ("u8 x[99999999999999999999999]", "Length is too large"),
+            ("dummy8 x[0]", "Length must be at least 1"),
+            ("fixstr x[0]", "Length must be at least 1"),
+            ("fixstrW x[99999999999999999999999]", "Length is too large"),
+        ] {
+            match parse_param_field_def(def) {
+                Err(DefParseError::ParseError(err)) => assert!(err.to_string().contains(message), "{}", err),
+                other => panic!("{} parsed as {:?}", def, other),
+            }
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let defs = [
+            "s8 a", "u8 isEnable:1 = 1", "s16 b:3 = -2", "u16 c", "s32 refId = -1", "u32 d:31", "b32 e = 1",
+            "f32 f = 0.5", "a32 g", "f64 h = -0.25", "fixstr i[16]", "fixstrW j[8]", "dummy8 k", "dummy8 l[3]",
+            "dummy8 m:7", "dummy8[4]", "f32 pos[3] = 1", "u32 n // tweak later",
+        ];
+        for def in defs {
+            let parsed = parse_param_field_def(def).expect("parses");
+            assert_eq!(parsed.to_string(), def);
+            assert_eq!(parse_param_field_def(parsed.to_string()).expect("parses"), parsed);
+        }
+        assert_eq!(parse_param_field_def("angle32 x=1.0").expect("parses").to_string(), "a32 x = 1");
+    }
+
+    #[test]
+    fn ignore_unknown_suffixes() {
+        let lenient = DefParseOptions { ignore_unknown_suffixes: true };
+        assert!(parse_param_field_def("u8 isEnable:1 @flag").is_err());
+        let (parsed, skipped) = parse_with_skipped("u8 isEnable:1 @flag // enabled", &lenient).expect("parses");
+        assert_eq!(parsed.field_type, ParamFieldType::u8 { bit_size: Some(1) });
+        assert_eq!(parsed.comment.as_deref(), Some("enabled"));
+        assert_eq!(skipped.as_deref(), Some("@flag"));
+
+        let (parsed, skipped) = parse_with_skipped("f32 speed = 1.5 <since=1.04>", &lenient).expect("parses");
+        assert_eq!(parsed.default_value, Some(1.5));
+        assert_eq!(skipped.as_deref(), Some("<since=1.04>"));
+        assert_eq!(parse_with_skipped("u32 id", &lenient).expect("parses").1, None);
+        assert!(parse_param_field_def_with_options("u64 id @flag", &lenient).is_err());
+    }
+
+    #[test]
+    fn dummy_anonymous() {
+        assert_eq!(
+            parse_param_field_def("dummy8[4]").expect("parses"),
+            ParamFieldDef {
+                name: String::new(),
+                default_value: None,
+                field_type: ParamFieldType::dummy8 { length: Some(DummyType::Bytes(4)) },
+                array_len: None,
+                comment: None,
+            }
+        );
+        assert_eq!(parse_param_field_def("dummy8:3").expect("parses").field_type, ParamFieldType::dummy8 { length: Some(DummyType::Bits(3)) });
+        assert!(parse_param_field_def("u8[4]").is_err());
+    }
+
     #[test]
     fn dummy_with_array_and_default() {
         let def = "dummy8 pad_3[16] = -1";
@@ -221,6 +475,8 @@ mod tests {
                 name: "pad_3".to_string(),
                 default_value: Some(-1.0),
                 field_type: ParamFieldType::dummy8 { length: Some(DummyType::Bytes(16)) },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -233,6 +489,8 @@ mod tests {
                 name: "testingVar".to_string(),
                 default_value: None,
                 field_type: ParamFieldType::u32 { bit_size: None },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -244,7 +502,9 @@ mod tests {
             ParamFieldDef {
                 name: "testingVar2".to_string(),
                 default_value: None,
-                field_type: ParamFieldType::s32,
+                field_type: ParamFieldType::s32 { bit_size: None },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -257,6 +517,8 @@ mod tests {
                 name: "ｇradFactor".to_string(),
                 default_value: None,
                 field_type: ParamFieldType::f32,
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -269,14 +531,59 @@ mod tests {
                 name: "testingVar".to_string(),
                 default_value: None,
                 field_type: ParamFieldType::u32 { bit_size: Some(3) },
+                array_len: None,
+                comment: None,
             }
         )
     }
 
     #[test]
-    #[should_panic]
     fn simple_bitsize_s() {
-        parse_param_field_def("s32 testingVar:3").expect("parses");
+        assert_eq!(
+            parse_param_field_def("s16 temperature:12").expect("parses"),
+            ParamFieldDef {
+                name: "temperature".to_string(),
+                default_value: None,
+                field_type: ParamFieldType::s16 { bit_size: Some(12) },
+                array_len: None,
+                comment: None,
+            }
+        )
+    }
+
+    #[test]
+    #[should_panic]
+    fn simple_bitsize_f() {
+        parse_param_field_def("f32 testingVar:3").expect("parses");
+    }
+
+    #[test]
+    fn simple_bitsize_out_of_range() {
+        for (def, max) in [("u8 x:9", 8), ("u16 x:17", 16), ("s32 x:0", 32), ("u8 x:300", 8)] {
+            match parse_param_field_def(def) {
+                Err(DefParseError::ParseError(err)) => {
+                    assert!(err.to_string().contains(&format!("Bit size must be between 1 and {}", max)), "{}", err);
+                    let (start, _) = match err.location {
+                        pest::error::InputLocation::Span(span) => span,
+                        pest::error::InputLocation::Pos(pos) => (pos, pos),
+                    };
+                    assert_eq!(start, def.find(':').expect("colon"));
+                }
+                other => panic!("{} parsed as {:?}", def, other),
+            }
+        }
+        assert!(parse_param_field_def("u16 x:16").is_ok());
+    }
+
+    #[test]
+    fn dummy_bitsize_out_of_range() {
+        for def in ["dummy8 x:9", "dummy8 x:0", "dummy8 x:300"] {
+            match parse_param_field_def(def) {
+                Err(DefParseError::ParseError(err)) => assert!(err.to_string().contains("Bit size must be between 1 and 8"), "{}", err),
+                other => panic!("{} parsed as {:?}", def, other),
+            }
+        }
+        assert!(parse_param_field_def("dummy8 x:8").is_ok());
     }
 
     #[test]
@@ -287,10 +594,31 @@ mod tests {
                 name: "testingVar".to_string(),
                 default_value: Some(-3.0),
                 field_type: ParamFieldType::u32 { bit_size: None },
+                array_len: None,
+                comment: None,
             }
         )
     }
 
+    #[test]
+    fn default_separators() {
+        for def in ["u32 x=3", "u32 x = 3", "u32 x  =   3", "u32 x:=3", "u32 x := 3"] {
+            assert_eq!(
+                parse_param_field_def(def).expect("parses"),
+                ParamFieldDef {
+                    name: "x".to_string(),
+                    default_value: Some(3.0),
+                    field_type: ParamFieldType::u32 { bit_size: None },
+                    array_len: None,
+                    comment: None,
+                },
+                "{}", def
+            )
+        }
+        assert_eq!(parse_param_field_def("u8 x:3:=1").expect("parses").default_value, Some(1.0));
+        assert_eq!(parse_param_field_def("dummy8 x[2]=0").expect("parses").default_value, Some(0.0));
+    }
+
     #[test]
     fn simple_default_s() {
         assert_eq!(
@@ -298,7 +626,9 @@ mod tests {
             ParamFieldDef {
                 name: "testingVar3".to_string(),
                 default_value: Some(-3.0),
-                field_type: ParamFieldType::s32,
+                field_type: ParamFieldType::s32 { bit_size: None },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -311,14 +641,24 @@ mod tests {
                 name: "testingVar".to_string(),
                 default_value: Some(0.0),
                 field_type: ParamFieldType::u32 { bit_size: Some(3) },
+                array_len: None,
+                comment: None,
             }
         )
     }
 
     #[test]
-    #[should_panic]
     fn simple_default_bitsize_s() {
-        parse_param_field_def("s32 testingVar:3 = 0").expect("parses");
+        assert_eq!(
+            parse_param_field_def("s32 testingVar:3 = -1").expect("parses"),
+            ParamFieldDef {
+                name: "testingVar".to_string(),
+                default_value: Some(-1.0),
+                field_type: ParamFieldType::s32 { bit_size: Some(3) },
+                array_len: None,
+                comment: None,
+            }
+        )
     }
 
     #[test]
@@ -328,7 +668,9 @@ mod tests {
             ParamFieldDef {
                 name: "texName_00".into(),
                 default_value: None,
-                field_type: ParamFieldType::fixstr { length: 16 }
+                field_type: ParamFieldType::fixstr { length: 16 },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -340,7 +682,9 @@ mod tests {
             ParamFieldDef {
                 name: "texName_00".into(),
                 default_value: None,
-                field_type: ParamFieldType::fixstrW { length: 16 }
+                field_type: ParamFieldType::fixstrW { length: 16 },
+                array_len: None,
+                comment: None,
             }
         )
     }
@@ -366,14 +710,11 @@ mod tests {
                 };
             let doc = roxmltree::Document::parse(string.as_str()).expect("parsing xml");
             for def_str in doc.descendants().filter(|a| a.has_tag_name("Field")).map(|a| a.attribute("Def").expect("def")) {
-                match parse_param_field_def(def_str) {
-                    Err(err) => {
-                        if !errors.contains_key(path) {
-                            errors.insert(path.to_owned(), Vec::new());
-                        }
-                        errors.get_mut(path).expect("wewe").push((def_str.to_owned(), err));
+                if let Err(err) = parse_param_field_def(def_str) {
+                    if !errors.contains_key(path) {
+                        errors.insert(path.to_owned(), Vec::new());
                     }
-                    _ => {}
+                    errors.get_mut(path).expect("wewe").push((def_str.to_owned(), err));
                 }
             }
         }
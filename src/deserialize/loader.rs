@@ -0,0 +1,59 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use crate::deserialize::{decode, deserialize_def_inner, DeserializeOptions, DeserializeWarning, ParamdefDeserializeError};
+use crate::ParamDef;
+
+/// Deserializes many [ParamDef]s in a row, reusing its buffers between them.
+///
+/// Create one loader and call [`ParamdexLoader::load_file`] or [`ParamdexLoader::load_str`] for
+/// each def, such as every file of a Paramdex dump. File contents are read into the same buffer
+/// each time, which grows to fit the largest def and is then reused without reallocating.
+///
+/// ```no_run
+/// # use paramdex_rs::deserialize::ParamdexLoader;
+/// let mut loader = ParamdexLoader::new();
+/// for path in ["Defs/EquipParamWeapon.xml", "Defs/EquipParamGoods.xml"] {
+///     let paramdef = loader.load_file(path)?;
+///     println!("{}: {} fields", paramdef.param_type, paramdef.field_count());
+/// }
+/// # Ok::<(), paramdex_rs::deserialize::ParamdefDeserializeError>(())
+/// ```
+#[derive(Default, Debug)]
+pub struct ParamdexLoader {
+    opts: DeserializeOptions,
+    buffer: Vec<u8>,
+    warnings: Vec<DeserializeWarning>,
+}
+
+impl ParamdexLoader {
+    /// Creates a loader using the default options, matching [`deserialize_def`](crate::deserialize::deserialize_def).
+    pub fn new() -> ParamdexLoader {
+        ParamdexLoader::default()
+    }
+
+    /// Creates a loader using the given options for every def.
+    pub fn with_options(opts: DeserializeOptions) -> ParamdexLoader {
+        ParamdexLoader { opts, ..Default::default() }
+    }
+
+    /// Reads and deserializes the def in a file.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<ParamDef, ParamdefDeserializeError> {
+        self.buffer.clear();
+        File::open(path)?.read_to_end(&mut self.buffer)?;
+        self.warnings.clear();
+        let input = decode(&self.buffer)?;
+        deserialize_def_inner(&input, &self.opts, &mut self.warnings)
+    }
+
+    /// Deserializes a def from a string.
+    pub fn load_str(&mut self, input: &str) -> Result<ParamDef, ParamdefDeserializeError> {
+        self.warnings.clear();
+        deserialize_def_inner(input, &self.opts, &mut self.warnings)
+    }
+
+    /// The non-fatal quirks found while loading the last def.
+    pub fn warnings(&self) -> &[DeserializeWarning] {
+        &self.warnings
+    }
+}
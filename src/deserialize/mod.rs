@@ -1,16 +1,41 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, Read};
 use std::num::{ParseFloatError, ParseIntError};
+use std::path::PathBuf;
 use std::str::{FromStr, ParseBoolError};
-use roxmltree::Node;
+use roxmltree::{Node, TextPos};
 use thiserror::Error;
-use crate::{EditFlags, ParamDef, ParamdefEndian, ParamdefFormat, ParamField, ParamFieldDef};
+use crate::display_format::DisplayFormat;
+use crate::{EditFlags, EnumRef, ParamDef, ParamDefHeader, ParamdefEndian, ParamdefFormat, ParamField, ParamFieldDef, ParamFieldType};
 
 mod field_def_parse;
+mod loader;
 
-pub use field_def_parse::DefParseError;
+pub use field_def_parse::{parse_param_field_def_with_options, DefParseError, DefParseOptions};
+pub use loader::ParamdexLoader;
 
 /// Root element name of a ParamDef in the XML
-const PARAM_DEF_ROOT: &'static str = "PARAMDEF";
+const PARAM_DEF_ROOT: &str = "PARAMDEF";
+
+/// Root child element holding the Field elements
+const FIELDS_ELEMENT: &str = "Fields";
+
+/// Root child elements that must be present and non-empty
+const REQUIRED_ROOT_ELEMENTS: [&str; 5] = ["ParamType", "DataVersion", "BigEndian", "Unicode", "FormatVersion"];
+
+/// Child elements of a Field that are read into a [ParamField]
+const KNOWN_FIELD_ELEMENTS: [&str; 9] = [
+    "DisplayName", "Enum", "Description", "DisplayFormat", "EditFlags", "Minimum", "Maximum", "Increment", "SortID",
+];
+
+/// Byte order mark that some editors save at the start of UTF-8 files
+const BOM: char = '\u{feff}';
+
+/// Tokens recognized in the EditFlags element
+const KNOWN_EDIT_FLAGS: [&str; 3] = ["None", "Wrap", "Lock"];
 
 impl FromStr for ParamDef {
     type Err = ParamdefDeserializeError;
@@ -22,75 +47,358 @@ impl FromStr for ParamDef {
 
 /// Deserialize a ParamDef from a provided XML string
 pub fn deserialize_def<S: AsRef<str>>(input: S) -> Result<ParamDef, ParamdefDeserializeError> {
-    let input = input.as_ref();
+    deserialize_def_inner(input.as_ref(), &DeserializeOptions::default(), &mut Vec::new())
+}
+
+/// Options for deserializing a ParamDef.
+///
+/// The default options match [`deserialize_def`].
+#[derive(Clone, Debug)]
+pub struct DeserializeOptions {
+    /// Options for the XML parser
+    pub xml: XmlOptions,
+
+    /// Name of the root element, `PARAMDEF` by default. Only meant for interop with nonstandard
+    /// exporters.
+    pub root_tag: String,
+
+    /// Name of the element holding the Field elements, `Fields` by default. Only meant for
+    /// interop with nonstandard exporters.
+    pub fields_tag: String,
+
+    /// What to do with fields of a type this crate doesn't recognize
+    pub on_unknown_type: OnUnknownType,
+
+    /// Options for parsing each field's Def attribute
+    pub def: DefParseOptions,
+
+    /// Elements to read a field's display name from when it has neither a `DisplayName` element
+    /// nor a `DisplayName` attribute, such as `DisplayNameEN`, tried in order. Empty by default.
+    ///
+    /// A `DisplayName` element takes precedence over a `DisplayName` attribute, which takes
+    /// precedence over these elements. They are not reported as unknown elements.
+    pub display_name_fallbacks: Vec<String>,
+
+    /// Whether to keep every attribute and child element of each Field in [`ParamField::raw`].
+    /// Off by default, as it keeps a second copy of the text of every field.
+    pub keep_raw: bool,
+}
+
+/// How to handle fields with an unrecognized type, such as types introduced by a newer game
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum OnUnknownType {
+    /// Fail with [`DefParseError::UnrecognizedType`]
+    #[default]
+    Error,
+
+    /// Leave the field out of the def, reporting [`DeserializeWarning::SkippedField`]. The
+    /// remaining fields will not have the offsets of the game's layout.
+    SkipField,
+
+    /// Leave the whole def out, reporting [`DeserializeWarning::SkippedDef`]. This only has an
+    /// effect when deserializing a whole Paramdex, with
+    /// [`Paramdex::deserialize_all_with_options`](crate::Paramdex::deserialize_all_with_options);
+    /// a single def still fails, as there is nothing to return.
+    SkipDef,
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> Self {
+        DeserializeOptions {
+            xml: XmlOptions::default(),
+            root_tag: PARAM_DEF_ROOT.into(),
+            fields_tag: FIELDS_ELEMENT.into(),
+            on_unknown_type: OnUnknownType::Error,
+            def: DefParseOptions::default(),
+            display_name_fallbacks: Vec::new(),
+            keep_raw: false,
+        }
+    }
+}
+
+/// Options for the XML parser used when deserializing.
+///
+/// The default options match [`deserialize_def`].
+#[derive(Clone, Default, Debug)]
+pub struct XmlOptions {
+    /// Whether to accept a document type definition (DTD) in the input. Inputs with a DTD are
+    /// rejected by default.
+    pub allow_dtd: bool,
+}
+
+impl XmlOptions {
+    fn parsing_options(&self) -> roxmltree::ParsingOptions {
+        roxmltree::ParsingOptions { allow_dtd: self.allow_dtd }
+    }
+}
+
+/// Deserialize a ParamDef from a provided XML string, using the given options.
+pub fn deserialize_def_with_options<S: AsRef<str>>(input: S, opts: &DeserializeOptions) -> Result<ParamDef, ParamdefDeserializeError> {
+    deserialize_def_inner(input.as_ref(), opts, &mut Vec::new())
+}
+
+/// Deserialize a ParamDef from a JSON string, for mixing JSON sources with XML ones in a
+/// [Paramdex](crate::Paramdex). Requires the `serde` feature.
+///
+/// The supported schema is the one written by [`ParamDef::to_json`]: an object with the header
+/// values and a `fields` array, where each field holds its def string under `def` alongside its
+/// metadata. Errors locate the offending value relative to the def, such as `fields[3].def`.
+#[cfg(feature = "serde")]
+pub fn deserialize_def_json<S: AsRef<str>>(input: S) -> Result<ParamDef, crate::json::JsonError> {
+    let json: serde_json::Value = serde_json::from_str(input.as_ref())?;
+    crate::json::paramdef_from_json(&json, "")
+}
+
+/// Deserialize a ParamDef from a provided XML string, also returning any non-fatal quirks
+/// found along the way.
+///
+/// # See also
+/// [`deserialize_def`] - For callers that don't need the warnings
+pub fn deserialize_def_with_warnings<S: AsRef<str>>(input: S) -> Result<(ParamDef, Vec<DeserializeWarning>), ParamdefDeserializeError> {
+    let mut warnings = Vec::new();
+    let paramdef = deserialize_def_inner(input.as_ref(), &DeserializeOptions::default(), &mut warnings)?;
+    Ok((paramdef, warnings))
+}
+
+/// Deserialize a ParamDef from an already parsed XML document, such as one that is also
+/// inspected for other metadata, without parsing the XML again.
+///
+/// # See also
+/// [`deserialize_def`] - For the common case of deserializing from a string
+pub fn deserialize_def_from_document(doc: &roxmltree::Document) -> Result<ParamDef, ParamdefDeserializeError> {
+    deserialize_document_inner(doc, &DeserializeOptions::default(), &mut Vec::new())
+}
+
+pub(crate) fn deserialize_def_inner(input: &str, opts: &DeserializeOptions, warnings: &mut Vec<DeserializeWarning>) -> Result<ParamDef, ParamdefDeserializeError> {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    let doc = roxmltree::Document::parse_with_options(input, opts.xml.parsing_options())?;
+    deserialize_document_inner(&doc, opts, warnings)
+}
+
+fn deserialize_document_inner(doc: &roxmltree::Document, opts: &DeserializeOptions, warnings: &mut Vec<DeserializeWarning>) -> Result<ParamDef, ParamdefDeserializeError> {
+    let (header, fields_node) = parse_root(doc, opts, warnings)?;
+    let fields_node = fields_node.ok_or_else(|| ParamdefDeserializeError::MissingParamData(opts.fields_tag.clone()))?;
 
-    let doc = roxmltree::Document::parse(input)?;
+    let ParamDefHeader { param_type, data_version, endian, string_format, format_version } = header;
+    let mut paramdef = ParamDef { param_type, data_version, endian, string_format, format_version, fields: Vec::new() };
+    let param_type = &paramdef.param_type;
+    let fields = &mut paramdef.fields;
 
+    for (index, node) in fields_node.children().filter(|a| a.has_tag_name("Field")).enumerate() {
+        match (parse_field_node(node, index, opts, warnings), opts.on_unknown_type) {
+            (Ok(field), _) => fields.push(field),
+            (Err(ParamdefDeserializeError::ParsingDefString(DefParseError::UnrecognizedType(field_type))), OnUnknownType::SkipField) => {
+                warnings.push(DeserializeWarning::SkippedField { param_type: param_type.clone(), index, field_type });
+            }
+            (Err(ParamdefDeserializeError::ParsingDefString(DefParseError::UnrecognizedType(field_type))), OnUnknownType::SkipDef) => {
+                warnings.push(DeserializeWarning::SkippedDef { param_type: param_type.clone(), field_type: field_type.clone() });
+                return Err(DefParseError::UnrecognizedType(field_type).into());
+            }
+            (Err(err), _) => return Err(err),
+        }
+    }
+
+    Ok(paramdef)
+}
+
+/// Deserialize only the scalar metadata of a ParamDef from a provided XML string, without parsing
+/// any of its fields. Useful for quickly indexing many defs by param type.
+pub fn deserialize_header<S: AsRef<str>>(input: S) -> Result<ParamDefHeader, ParamdefDeserializeError> {
+    let input = input.as_ref();
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    let opts = DeserializeOptions::default();
+    let doc = roxmltree::Document::parse_with_options(input, opts.xml.parsing_options())?;
+    parse_root(&doc, &opts, &mut Vec::new()).map(|(header, _)| header)
+}
+
+/// Reads the scalar elements under the root into a header, also returning the element holding
+/// the fields if there is one
+fn parse_root<'a, 'input>(doc: &'a roxmltree::Document<'input>, opts: &DeserializeOptions, warnings: &mut Vec<DeserializeWarning>)
+    -> Result<(ParamDefHeader, Option<Node<'a, 'input>>), ParamdefDeserializeError> {
     let root = doc.root_element();
-    if root.tag_name().name() != PARAM_DEF_ROOT {
-        return Err(ParamdefDeserializeError::MissingParamData("Invalid root element".into()));
+    if root.tag_name().name() != opts.root_tag {
+        return Err(ParamdefDeserializeError::MissingParamData(opts.root_tag.clone()));
     }
 
     let mut root_config: HashMap<String, String> = HashMap::new();
 
     let mut fields: Option<Node> = None;
 
-    for child in root.children() {
+    for child in root.children().filter(|a| a.is_element()) {
         match child.tag_name().name() {
-            "Fields" => {
+            name if name == opts.fields_tag => {
                 fields.replace(child);
             }
-            name => {
-                root_config.insert(name.into(), child.text().ok_or(ParamdefDeserializeError::XmlBlankElement(name.into()))?.into());
+            name => match child.text() {
+                _ if !REQUIRED_ROOT_ELEMENTS.contains(&name) => {
+                    warnings.push(DeserializeWarning::UnknownRootElement { element: name.into() });
+                }
+                Some(text) => {
+                    root_config.insert(name.into(), text.trim().into());
+                }
+                None => {
+                    return Err(ParamdefDeserializeError::XmlBlankElement {
+                        element: name.into(),
+                        pos: doc.text_pos_at(child.range().start),
+                    });
+                }
             }
         }
     }
 
-    let fields_node = fields.ok_or(ParamdefDeserializeError::MissingParamData("Fields".into()))?;
+    let param_type = get_or_error(&root_config, "ParamType")?;
+    if param_type.is_empty() {
+        return Err(ParamdefDeserializeError::MissingParamData("ParamType".into()));
+    }
 
-    let mut paramdef = ParamDef {
-        param_type: get_or_error(&root_config, "ParamType").cloned()?,
-        data_version: u32::from_str(get_or_error(&root_config, "DataVersion")?)?,
+    let header = ParamDefHeader {
+        param_type: param_type.clone(),
+        data_version: parse_version(&root_config, "DataVersion")?,
         endian: ParamdefEndian::from_str(get_or_error(&root_config, "BigEndian")?)?,
-        string_format: ParamdefFormat::from_str(get_or_error(&root_config, "BigEndian")?)?,
-        format_version: u32::from_str(get_or_error(&root_config, "FormatVersion")?)?,
-        fields: Vec::new()
+        string_format: ParamdefFormat::from_str(get_or_error(&root_config, "Unicode")?)?,
+        format_version: parse_version(&root_config, "FormatVersion")?,
     };
+    Ok((header, fields))
+}
 
-    let fields = &mut paramdef.fields;
+fn parse_version(root_config: &HashMap<String, String>, element: &str) -> Result<u32, ParamdefDeserializeError> {
+    u32::from_str(get_or_error(root_config, element)?).map_err(|source| ParamdefDeserializeError::XmlParsingElementNumber {
+        element: element.into(),
+        source,
+    })
+}
 
-    for node in fields_node.children().filter(|a| a.has_tag_name("Field")) {
-        fields.push(parse_field_node(node)?);
+/// Deserialize a ParamDef from a reader of XML, such as an open file.
+///
+/// The whole input is read into memory before parsing, as the XML parser needs the full document.
+/// Input must be UTF-8, unless the `encoding` feature is enabled, in which case the encoding
+/// declared in the XML declaration (such as `encoding="shift_jis"`) is decoded as well.
+pub fn deserialize_def_from_reader<R: Read>(mut reader: R) -> Result<ParamDef, ParamdefDeserializeError> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    deserialize_def(decode(&input)?)
+}
+
+/// Lazily deserialize ParamDefs from files, reading and parsing each path only as the iterator
+/// is advanced. Errors carry the path of the file that failed, and iteration can continue past
+/// them or stop at the first.
+pub fn defs_from_paths<I: IntoIterator<Item = PathBuf>>(paths: I) -> impl Iterator<Item = Result<ParamDef, FileDeserializeError>> {
+    paths.into_iter().map(|path| {
+        match File::open(&path).map_err(ParamdefDeserializeError::from).and_then(deserialize_def_from_reader) {
+            Ok(paramdef) => Ok(paramdef),
+            Err(source) => Err(FileDeserializeError { path, source: Box::new(source) }),
+        }
+    })
+}
+
+#[cfg(not(feature = "encoding"))]
+pub(crate) fn decode(input: &[u8]) -> Result<Cow<'_, str>, io::Error> {
+    std::str::from_utf8(input).map(Cow::Borrowed).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Decodes input in its declared encoding, defaulting to UTF-8. A byte order mark takes
+/// precedence over the declaration.
+#[cfg(feature = "encoding")]
+pub(crate) fn decode(input: &[u8]) -> Result<Cow<'_, str>, io::Error> {
+    let encoding = declared_encoding(input)
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, encoding, had_errors) = encoding.decode(input);
+    if had_errors {
+        let message = format!("Input is not valid {}", encoding.name());
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
     }
+    Ok(text)
+}
 
-    Ok(paramdef)
+/// The encoding label from the XML declaration, if the input starts with one. The declaration
+/// is ASCII in every encoding a def could reasonably use.
+#[cfg(feature = "encoding")]
+fn declared_encoding(input: &[u8]) -> Option<&str> {
+    let input = input.strip_prefix(b"\xef\xbb\xbf").unwrap_or(input);
+    let end = input.windows(2).position(|a| a == b"?>")?;
+    let declaration = std::str::from_utf8(&input[..end]).ok()?.strip_prefix("<?xml")?;
+    let value = declaration.split_once("encoding")?.1.trim_start().strip_prefix('=')?.trim_start();
+    let quote = value.chars().next().filter(|a| matches!(a, '"' | '\''))?;
+    value[1..].split(quote).next()
+}
+
+/// Parses a boolean element, accepting `true` and `false` in any case as well as `1` and `0`
+fn parse_xml_bool(s: &str) -> Result<bool, ParseBoolError> {
+    match s {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        s => bool::from_str(s.to_lowercase().as_str()),
+    }
 }
 
 /// Wrapper function to return value from a map or an error
 fn get_or_error<'a>(map: &'a HashMap<String, String>, key: &str) -> Result<&'a String, ParamdefDeserializeError> {
-    map.get(key).ok_or(ParamdefDeserializeError::MissingParamData(format!("{}", key)))
+    map.get(key).ok_or(ParamdefDeserializeError::MissingParamData(key.to_string()))
 }
 
-fn parse_field_node(field_node: Node) -> Result<ParamField, ParamdefDeserializeError> {
-    let attr = field_node.attribute("Def").ok_or(ParamdefDeserializeError::MissingParamData("Field Def".into()))?;
+/// Parses a Field element, where `index` is its position among the Field elements
+fn parse_field_node(field_node: Node, index: usize, opts: &DeserializeOptions, warnings: &mut Vec<DeserializeWarning>) -> Result<ParamField, ParamdefDeserializeError> {
+    let attr = field_node.attribute("Def").ok_or(ParamdefDeserializeError::FieldMissingDef { index })?;
+    let (field_def, skipped) = field_def_parse::parse_with_skipped(attr, &opts.def)?;
+    if let Some(suffix) = skipped {
+        warnings.push(DeserializeWarning::IgnoredDefSuffix { field: field_def.name.clone(), suffix });
+    }
 
     let mut field_config: HashMap<String, String> = HashMap::new();
+    let mut descriptions: HashMap<String, String> = HashMap::new();
 
-    for child in field_node.children() {
-        if let Some(text) = child.text() {
-            field_config.insert(child.tag_name().name().into(), text.into());
+    for child in field_node.children().filter(|a| a.is_element()) {
+        let name = child.tag_name().name();
+        if let Some(language) = description_language(child) {
+            if let Some(text) = child.text().map(str::trim).filter(|a| !a.is_empty()) {
+                descriptions.insert(language.into(), text.replace("\r\n", "\n"));
+            }
+            continue;
+        }
+        if !KNOWN_FIELD_ELEMENTS.contains(&name) && !opts.display_name_fallbacks.iter().any(|a| a == name) {
+            warnings.push(DeserializeWarning::UnknownFieldElement {
+                field: field_def.name.clone(),
+                element: name.into(),
+            });
+        }
+        // Whitespace-only elements are treated the same as empty ones
+        if let Some(text) = child.text().map(str::trim).filter(|a| !a.is_empty()) {
+            field_config.insert(name.into(), text.into());
+        }
+    }
+
+    if let Some(edit_flags) = field_config.get("EditFlags") {
+        for flag in edit_flags.split(',').map(str::trim).filter(|a| !KNOWN_EDIT_FLAGS.contains(a)) {
+            warnings.push(DeserializeWarning::UnknownEditFlag {
+                field: field_def.name.clone(),
+                flag: flag.into(),
+            });
         }
     }
 
+    let display_format = field_config.get("DisplayFormat").and_then(|format| {
+        DisplayFormat::from_str(format)
+            .map_err(|_| warnings.push(DeserializeWarning::InvalidDisplayFormat {
+                field: field_def.name.clone(),
+                format: format.clone(),
+            }))
+            .ok()
+    });
 
     Ok(ParamField {
 
-        field_def: ParamFieldDef::from_str(attr)?,
+        field_def,
 
-        display_name: field_config.get("DisplayName").cloned(),
-        enum_tdf: field_config.get("Enum").cloned(),
-        description: field_config.get("Description").cloned(),
+        display_name: field_config.get("DisplayName").cloned()
+            .or_else(|| field_node.attribute("DisplayName").map(str::trim).filter(|a| !a.is_empty()).map(str::to_owned))
+            .or_else(|| opts.display_name_fallbacks.iter().find_map(|a| field_config.get(a)).cloned()),
+        enum_tdf: field_config.get("Enum").map(|a| EnumRef::from(a.as_str())),
+        // Line endings are normalized, as defs are edited on both Windows and Unix
+        description: field_config.get("Description").map(|a| a.replace("\r\n", "\n")),
+        descriptions,
         printf_format: field_config.get("DisplayFormat").cloned(),
+        display_format,
 
         edit_flags: field_config.get("EditFlags").map(|a| EditFlags::from_str(a)).swap()?, // TODO
 
@@ -98,10 +406,28 @@ fn parse_field_node(field_node: Node) -> Result<ParamField, ParamdefDeserializeE
         maximum: field_config.get("Maximum").map(|a| f64::from_str(a.as_str())).swap()?,
         increment: field_config.get("Increment").map(|a| f64::from_str(a.as_str())).swap()?,
         sort_id: field_config.get("SortID").map(|a| usize::from_str(a.as_str())).swap()?,
+        raw: if opts.keep_raw { raw_field(field_node) } else { HashMap::new() },
     })
 
 }
 
+/// Every attribute and child element of a Field element, as described on [`ParamField::raw`]
+fn raw_field(field_node: Node) -> HashMap<String, String> {
+    let attributes = field_node.attributes().iter().map(|a| (format!("@{}", a.name()), a.value().to_owned()));
+    let elements = field_node.children().filter(|a| a.is_element())
+        .map(|a| (a.tag_name().name().to_owned(), a.text().unwrap_or_default().to_owned()));
+    attributes.chain(elements).collect()
+}
+
+/// The language tag of a localized description element, either from a suffix on the element name
+/// such as `DescriptionJP`, or from a `lang` attribute on a `Description` element
+fn description_language<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    match node.tag_name().name().strip_prefix("Description")? {
+        "" => node.attribute("lang").filter(|a| !a.is_empty()),
+        language => Some(language),
+    }
+}
+
 impl FromStr for EditFlags {
     type Err = ParamdefDeserializeError;
 
@@ -121,6 +447,16 @@ impl FromStr for ParamFieldDef {
     }
 }
 
+impl TryFrom<&str> for ParamFieldType {
+    type Error = DefParseError;
+
+    /// Parses a bare type token such as `u32` or `fixstrW`, as it would appear in a def.
+    /// Bit sizes default to `None`, and string lengths to 0.
+    fn try_from(token: &str) -> Result<Self, Self::Error> {
+        field_def_parse::parse_field_type(token)
+    }
+}
+
 trait OptionResultExt<V, E> {
     fn swap(self) -> Result<Option<V>, E>;
 }
@@ -139,7 +475,7 @@ impl FromStr for ParamdefEndian {
     type Err = ParseBoolError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-          bool::from_str(s.to_lowercase().as_str()).map(|a| a.into())
+        parse_xml_bool(s).map(|a| a.into())
     }
 }
 
@@ -153,11 +489,22 @@ impl From<bool> for ParamdefEndian {
     }
 }
 
+impl ParamdefEndian {
+    /// The value of the `BigEndian` element for this endianness: `True` for big endian and
+    /// `False` for little endian, capitalized as in FromSoftware's defs.
+    pub fn to_xml_bool(&self) -> &'static str {
+        match self {
+            Self::Big => "True",
+            Self::Little => "False",
+        }
+    }
+}
+
 impl FromStr for ParamdefFormat {
     type Err = ParseBoolError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        bool::from_str(s.to_lowercase().as_str()).map(|a| a.into())
+        parse_xml_bool(s).map(|a| a.into())
     }
 }
 
@@ -171,26 +518,547 @@ impl From<bool> for ParamdefFormat {
     }
 }
 
+impl ParamdefFormat {
+    /// The value of the `Unicode` element for this format: `True` for UTF16 and `False` for
+    /// ShiftJIS, capitalized as in FromSoftware's defs.
+    pub fn to_xml_bool(&self) -> &'static str {
+        match self {
+            Self::UTF16 => "True",
+            Self::ShiftJIS => "False",
+        }
+    }
+}
+
+/// A non-fatal quirk found while deserializing a [ParamDef]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum DeserializeWarning {
+    /// An element under the root that isn't read into the [ParamDef]
+    UnknownRootElement {
+        element: String,
+    },
+
+    /// An element under a Field that isn't read into the [ParamField]
+    UnknownFieldElement {
+        /// The internal name of the field
+        field: String,
+        element: String,
+    },
+
+    /// A token in a field's EditFlags that doesn't correspond to an [EditFlags] member
+    UnknownEditFlag {
+        /// The internal name of the field
+        field: String,
+        flag: String,
+    },
+
+    /// A DisplayFormat that could not be parsed, which is kept only in its raw form
+    InvalidDisplayFormat {
+        /// The internal name of the field
+        field: String,
+        format: String,
+    },
+
+    /// Unrecognized text after a field's def that was skipped, with
+    /// [`DefParseOptions::ignore_unknown_suffixes`]
+    IgnoredDefSuffix {
+        /// The internal name of the field
+        field: String,
+        suffix: String,
+    },
+
+    /// A field with an unrecognized type that was left out, with [`OnUnknownType::SkipField`]
+    SkippedField {
+        param_type: String,
+        /// The position of the field among the Field elements
+        index: usize,
+        field_type: String,
+    },
+
+    /// A def with a field of an unrecognized type that was left out, with
+    /// [`OnUnknownType::SkipDef`]
+    SkippedDef {
+        param_type: String,
+        field_type: String,
+    },
+}
+
+impl Display for DeserializeWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRootElement { element } => write!(f, "Unknown root element {} was ignored", element),
+            Self::UnknownFieldElement { field, element } => write!(f, "Unknown element {} on field {} was ignored", element, field),
+            Self::UnknownEditFlag { field, flag } => write!(f, "Unknown edit flag {} on field {} was ignored", flag, field),
+            Self::InvalidDisplayFormat { field, format } => write!(f, "Display format {} on field {} could not be parsed", format, field),
+            Self::IgnoredDefSuffix { field, suffix } => write!(f, "Unknown suffix {} on field {} was ignored", suffix, field),
+            Self::SkippedField { param_type, index, field_type } => {
+                write!(f, "Field at index {} of {} was skipped, as its type {} is unrecognized", index, param_type, field_type)
+            }
+            Self::SkippedDef { param_type, field_type } => {
+                write!(f, "{} was skipped, as field type {} is unrecognized", param_type, field_type)
+            }
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ParamdefDeserializeError {
+    #[error("Reading input failed: {0}")]
+    Reading(#[from] std::io::Error),
+
     #[error("XML parsing failed: {0}")]
     XmlParsing(#[from] roxmltree::Error),
 
-    #[error("XML blank element: {0}")]
-    XmlBlankElement(String),
+    #[error("XML blank element: {element} at {pos}")]
+    XmlBlankElement {
+        /// The name of the blank element
+        element: String,
+
+        /// The position of the element in the input
+        pos: TextPos,
+    },
 
     #[error("Parsing number from XML: {0}")]
     XmlParsingNumber(#[from] ParseIntError),
 
+    #[error("Failed to parse {element} as u32: {source}")]
+    XmlParsingElementNumber {
+        /// The name of the element holding the number
+        element: String,
+
+        source: ParseIntError,
+    },
+
     #[error("Parsing bool from XML: {0}")]
     XmlParsingBool(#[from] ParseBoolError),
 
     #[error("Parsing float from XML: {0}")]
     XmlParsingFloat(#[from] ParseFloatError),
 
-    #[error("A required field in the XML was missing")]
+    #[error("Missing required element: {0}")]
     MissingParamData(String),
 
+    #[error("Field at index {index} has no Def attribute")]
+    FieldMissingDef {
+        /// The position of the field among the Field elements, matching [ParamDef::fields]
+        index: usize,
+    },
+
     #[error("Failed to parse field def string")]
     ParsingDefString(#[from] DefParseError)
 }
+
+/// An error deserializing a ParamDef from a file
+#[derive(Error, Debug)]
+#[error("Deserializing {} failed: {source}", .path.display())]
+pub struct FileDeserializeError {
+    /// The path of the file that failed
+    pub path: PathBuf,
+
+    /// The underlying error
+    pub source: Box<ParamdefDeserializeError>,
+}
+
+impl ParamdefDeserializeError {
+    /// The line and column in the input XML where the error occurred, if known.
+    pub fn position(&self) -> Option<TextPos> {
+        match self {
+            Self::XmlParsing(err) => Some(err.pos()),
+            Self::XmlBlankElement { pos, .. } => Some(*pos),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{EnumRef, Paramdex, ParamdefEndian, ParamdefFormat, ParamFieldType};
+    use roxmltree::TextPos;
+    use crate::deserialize::{defs_from_paths, deserialize_def_inner, DefParseOptions, deserialize_def, deserialize_def_from_document, deserialize_def_from_reader, deserialize_header, deserialize_def_with_options, deserialize_def_with_warnings, DefParseError, DeserializeOptions, DeserializeWarning, OnUnknownType, ParamdefDeserializeError, ParamdexLoader, XmlOptions};
+
+    const TEST_DEF: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<PARAMDEF XmlVersion="1">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <DataVersion>2</DataVersion>
+  <BigEndian>False</BigEndian>
+  <Unicode>True</Unicode>
+  <FormatVersion>203</FormatVersion>
+  <Fields>
+    <Field Def="s32 refId = -1">
+      <DisplayName>Reference ID</DisplayName>
+      <Description>ID of the referenced param</Description>
+      <Minimum>-1</Minimum>
+      <Maximum>1000000</Maximum>
+      <SortID>100</SortID>
+      <DisplayFormat>%d</DisplayFormat>
+    </Field>
+    <Field Def="u8 isEnable:1">
+      <DisplayName>Enabled</DisplayName>
+      <Enum>ON_OFF</Enum>
+      <EditFlags>Wrap, Lock</EditFlags>
+    </Field>
+    <Field Def="dummy8 pad:7" />
+    <Field Def="fixstrW name[16]" />
+  </Fields>
+</PARAMDEF>
+"#;
+
+    #[test]
+    fn deserialize() {
+        let def = deserialize_def(TEST_DEF).expect("deserializes");
+        assert_eq!(def.param_type, "TEST_PARAM_ST");
+        assert_eq!(def.data_version, 2);
+        assert_eq!(def.endian, ParamdefEndian::Little);
+        assert_eq!(def.string_format, ParamdefFormat::UTF16);
+        assert_eq!(def.format_version, 203);
+        assert_eq!(def.fields.len(), 4);
+        assert_eq!(def.fields[0].display_name.as_deref(), Some("Reference ID"));
+        assert_eq!(def.fields[0].minimum, Some(-1.0));
+        assert_eq!(def.fields[0].sort_id, Some(100));
+        assert_eq!(def.fields[0].display_format.as_ref().map(|a| a.conversion), Some('d'));
+        assert_eq!(def.fields[1].field_def.field_type, ParamFieldType::u8 { bit_size: Some(1) });
+        assert_eq!(def.fields[1].enum_tdf.as_ref().map(EnumRef::name), Some("ON_OFF"));
+        assert!(def.fields[1].edit_flags.as_ref().expect("edit flags").lock);
+    }
+
+    #[test]
+    fn string_format_from_unicode() {
+        let input = TEST_DEF.replace("<Unicode>True</Unicode>", "<Unicode>False</Unicode>");
+        let def = deserialize_def(input).expect("deserializes");
+        assert_eq!((def.endian, def.string_format), (ParamdefEndian::Little, ParamdefFormat::ShiftJIS));
+        let input = TEST_DEF.replace("<BigEndian>False</BigEndian>", "<BigEndian>True</BigEndian>");
+        let def = deserialize_def(input).expect("deserializes");
+        assert_eq!((def.endian, def.string_format), (ParamdefEndian::Big, ParamdefFormat::UTF16));
+    }
+
+    #[test]
+    fn deserialize_from_reader() {
+        let def = deserialize_def_from_reader(TEST_DEF.as_bytes()).expect("deserializes");
+        assert!(def == deserialize_def(TEST_DEF).expect("deserializes"));
+    }
+
+    #[test]
+    fn deserialize_from_document() {
+        let doc = roxmltree::Document::parse(TEST_DEF).expect("parses");
+        let def = deserialize_def_from_document(&doc).expect("deserializes");
+        assert!(def == deserialize_def(TEST_DEF).expect("deserializes"));
+        assert_eq!(doc.root_element().attribute("XmlVersion"), Some("1"));
+    }
+
+    #[test]
+    fn xml_error_position() {
+        let err = deserialize_def("<PARAMDEF>\n  <ParamType>A</DataVersion>\n</PARAMDEF>").expect_err("fails");
+        assert_eq!(err.position(), Some(TextPos::new(2, 15)));
+        assert!(err.to_string().contains("2:15"), "{}", err);
+    }
+
+    #[test]
+    fn version_errors_name_element() {
+        for element in ["DataVersion", "FormatVersion"] {
+            let input = TEST_DEF.replacen(&format!("<{}>", element), &format!("<{}>-", element), 1);
+            let err = deserialize_def(&input).expect_err("fails");
+            assert!(matches!(&err, ParamdefDeserializeError::XmlParsingElementNumber { element: a, .. } if a == element));
+            assert!(err.to_string().starts_with(&format!("Failed to parse {} as u32: ", element)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn lazy_defs_from_paths() {
+        let dir = std::env::temp_dir().join(format!("paramdex-rs-defs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("creates directory");
+        std::fs::write(dir.join("TEST_PARAM_ST.xml"), TEST_DEF).expect("writes def");
+        std::fs::write(dir.join("Broken.xml"), "<PARAMDEF></Fields>").expect("writes def");
+
+        let paths = ["TEST_PARAM_ST.xml", "Missing.xml", "Broken.xml"].map(|a| dir.join(a));
+        let results: Vec<_> = defs_from_paths(paths).collect();
+        std::fs::remove_dir_all(&dir).expect("removes directory");
+
+        assert_eq!(results[0].as_ref().expect("deserializes").param_type, "TEST_PARAM_ST");
+        let missing = results[1].as_ref().expect_err("fails");
+        assert_eq!(missing.path, dir.join("Missing.xml"));
+        assert!(matches!(*missing.source, ParamdefDeserializeError::Reading(_)));
+        let broken = results[2].as_ref().expect_err("fails");
+        assert_eq!(broken.path, dir.join("Broken.xml"));
+        assert!(matches!(*broken.source, ParamdefDeserializeError::XmlParsing(_)));
+    }
+
+    #[test]
+    fn unknown_type_policy() {
+        let newer = TEST_DEF.replacen("TEST_PARAM_ST", "NEWER_PARAM_ST", 1).replacen("dummy8 pad:7", "u64 value", 1);
+        let inputs = [TEST_DEF, newer.as_str()];
+        assert!(matches!(
+            Paramdex::deserialize_all(inputs),
+            Err(ParamdefDeserializeError::ParsingDefString(DefParseError::UnrecognizedType(_)))
+        ));
+
+        let opts = DeserializeOptions { on_unknown_type: OnUnknownType::SkipField, ..Default::default() };
+        let (paramdex, warnings) = Paramdex::deserialize_all_with_options(inputs, &opts).expect("deserializes");
+        let newer = paramdex.get_param_def("NEWER_PARAM_ST").expect("present");
+        assert_eq!(newer.field_count(), 3);
+        assert_eq!(warnings, vec![DeserializeWarning::SkippedField {
+            param_type: "NEWER_PARAM_ST".into(),
+            index: 2,
+            field_type: "u64".into(),
+        }]);
+
+        let opts = DeserializeOptions { on_unknown_type: OnUnknownType::SkipDef, ..Default::default() };
+        let (paramdex, warnings) = Paramdex::deserialize_all_with_options(inputs, &opts).expect("deserializes");
+        assert!(paramdex.get_param_def("TEST_PARAM_ST").is_some());
+        assert!(paramdex.get_param_def("NEWER_PARAM_ST").is_none());
+        assert_eq!(warnings, vec![DeserializeWarning::SkippedDef { param_type: "NEWER_PARAM_ST".into(), field_type: "u64".into() }]);
+    }
+
+    #[test]
+    fn multi_line_description() {
+        let description = "Behavior when hit.&#13;&#10;0: None&#13;&#10;1: Knockback";
+        let input = TEST_DEF.replacen("ID of the referenced param", description, 1);
+        let paramdef = deserialize_def(input).expect("deserializes");
+        let field = &paramdef.fields[0];
+        assert_eq!(field.description.as_deref(), Some("Behavior when hit.\n0: None\n1: Knockback"));
+        assert_eq!(field.description_lines().collect::<Vec<_>>(), vec!["Behavior when hit.", "0: None", "1: Knockback"]);
+        assert_eq!(paramdef.fields[1].description_lines().count(), 0);
+    }
+
+    #[test]
+    fn localized_descriptions() {
+        let localized = "<Description>ID of the referenced param</Description>
+      <DescriptionJP>参照先のID</DescriptionJP>
+      <DescriptionXX>Unknown language</DescriptionXX>
+      <Description lang=\"de\">Referenzierte ID</Description>";
+        let input = TEST_DEF.replacen("<Description>ID of the referenced param</Description>", localized, 1);
+        let (paramdef, warnings) = deserialize_def_with_warnings(input).expect("deserializes");
+        let field = &paramdef.fields[0];
+        assert_eq!(field.description.as_deref(), Some("ID of the referenced param"));
+        assert_eq!(field.descriptions.len(), 3);
+        assert_eq!(field.descriptions["JP"], "参照先のID");
+        assert_eq!(field.descriptions["XX"], "Unknown language");
+        assert_eq!(field.descriptions["de"], "Referenzierte ID");
+        assert!(warnings.is_empty());
+        assert!(paramdef.fields[1].descriptions.is_empty());
+    }
+
+    #[test]
+    fn xml_bool_spellings() {
+        for spelling in ["true", "True", "TRUE", "1"] {
+            assert_eq!(ParamdefEndian::from_str(spelling), Ok(ParamdefEndian::Big));
+            assert_eq!(ParamdefFormat::from_str(spelling), Ok(ParamdefFormat::UTF16));
+        }
+        for spelling in ["false", "False", "0"] {
+            assert_eq!(ParamdefEndian::from_str(spelling), Ok(ParamdefEndian::Little));
+            assert_eq!(ParamdefFormat::from_str(spelling), Ok(ParamdefFormat::ShiftJIS));
+        }
+        for spelling in ["yes", "2", ""] {
+            assert!(ParamdefEndian::from_str(spelling).is_err());
+        }
+    }
+
+    #[test]
+    fn xml_bools() {
+        assert_eq!(ParamdefEndian::Big.to_xml_bool(), "True");
+        assert_eq!(ParamdefEndian::Little.to_xml_bool(), "False");
+        assert_eq!(ParamdefFormat::UTF16.to_xml_bool(), "True");
+        assert_eq!(ParamdefFormat::ShiftJIS.to_xml_bool(), "False");
+        for endian in [ParamdefEndian::Big, ParamdefEndian::Little] {
+            assert_eq!(ParamdefEndian::from_str(endian.to_xml_bool()), Ok(endian));
+        }
+        for format in [ParamdefFormat::UTF16, ParamdefFormat::ShiftJIS] {
+            assert_eq!(ParamdefFormat::from_str(format.to_xml_bool()), Ok(format));
+        }
+    }
+
+    #[test]
+    fn header_only() {
+        let header = deserialize_header(TEST_DEF).expect("deserializes");
+        assert_eq!(header, deserialize_def(TEST_DEF).expect("deserializes").header());
+        assert_eq!(header.param_type, "TEST_PARAM_ST");
+        assert_eq!(header.data_version, 2);
+
+        let unparseable_fields = TEST_DEF.replacen("dummy8 pad:7", "not a def", 1);
+        assert!(deserialize_def(&unparseable_fields).is_err());
+        assert_eq!(deserialize_header(&unparseable_fields).expect("deserializes"), header);
+    }
+
+    #[test]
+    fn reused_loader() {
+        let mut loader = ParamdexLoader::new();
+        let with_unknown_element = TEST_DEF.replacen("<Fields>", "<Comment>Test</Comment>\n  <Fields>", 1);
+        assert_eq!(loader.load_str(&with_unknown_element).expect("deserializes").param_type, "TEST_PARAM_ST");
+        assert_eq!(loader.warnings().len(), 1);
+
+        let path = std::env::temp_dir().join(format!("paramdex-rs-loader-{}.xml", std::process::id()));
+        std::fs::write(&path, TEST_DEF).expect("writes def");
+        let paramdef = loader.load_file(&path);
+        std::fs::remove_file(&path).expect("removes def");
+        assert_eq!(paramdef.expect("deserializes"), deserialize_def(TEST_DEF).expect("deserializes"));
+        assert!(loader.warnings().is_empty());
+    }
+
+    #[test]
+    fn custom_tag_names() {
+        let input = TEST_DEF.replace("PARAMDEF", "ParamDef").replace("Fields>", "FieldList>");
+        assert!(deserialize_def(&input).is_err());
+
+        let opts = DeserializeOptions { root_tag: "ParamDef".into(), fields_tag: "FieldList".into(), ..Default::default() };
+        let paramdef = deserialize_def_with_options(&input, &opts).expect("deserializes");
+        assert_eq!(paramdef, deserialize_def(TEST_DEF).expect("deserializes"));
+    }
+
+    #[test]
+    fn ignore_unknown_def_suffixes() {
+        let input = TEST_DEF.replacen(r#"Def="u8 isEnable:1""#, r#"Def="u8 isEnable:1 @flag""#, 1);
+        assert!(matches!(deserialize_def(&input), Err(ParamdefDeserializeError::ParsingDefString(_))));
+
+        let opts = DeserializeOptions { def: DefParseOptions { ignore_unknown_suffixes: true }, ..Default::default() };
+        let mut warnings = Vec::new();
+        let paramdef = deserialize_def_inner(&input, &opts, &mut warnings).expect("deserializes");
+        assert_eq!(paramdef, deserialize_def(TEST_DEF).expect("deserializes"));
+        assert_eq!(warnings, vec![DeserializeWarning::IgnoredDefSuffix { field: "isEnable".into(), suffix: "@flag".into() }]);
+    }
+
+    #[test]
+    fn display_name_fallbacks() {
+        let input = TEST_DEF
+            .replacen(r#"<Field Def="dummy8 pad:7" />"#, r#"<Field Def="dummy8 pad:7" DisplayName="Padding" />"#, 1)
+            .replacen(r#"<Field Def="fixstrW name[16]" />"#, r#"<Field Def="fixstrW name[16]"><DisplayNameEN>Name</DisplayNameEN></Field>"#, 1)
+            .replacen(r#"<Field Def="s32 refId = -1">"#, r#"<Field Def="s32 refId = -1" DisplayName="Ignored">"#, 1);
+        let (paramdef, warnings) = deserialize_def_with_warnings(&input).expect("deserializes");
+        let display_names: Vec<Option<&str>> = paramdef.fields.iter().map(|a| a.display_name.as_deref()).collect();
+        assert_eq!(display_names, vec![Some("Reference ID"), Some("Enabled"), Some("Padding"), None]);
+        assert_eq!(warnings, vec![DeserializeWarning::UnknownFieldElement { field: "name".into(), element: "DisplayNameEN".into() }]);
+
+        let opts = DeserializeOptions { display_name_fallbacks: vec!["DisplayNameJP".into(), "DisplayNameEN".into()], ..Default::default() };
+        let mut warnings = Vec::new();
+        let paramdef = deserialize_def_inner(&input, &opts, &mut warnings).expect("deserializes");
+        assert_eq!(paramdef.fields[3].display_name.as_deref(), Some("Name"));
+        assert!(warnings.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_json() {
+        use crate::deserialize::deserialize_def_json;
+        use crate::json::JsonError;
+
+        let paramdef = deserialize_def(TEST_DEF).expect("deserializes");
+        let json = paramdef.to_json().to_string();
+        assert_eq!(deserialize_def_json(&json).expect("deserializes"), paramdef);
+
+        let input = r#"{"param_type": "A_PARAM_ST", "data_version": 1, "big_endian": false, "unicode": true,
+            "format_version": 203, "fields": [{"def": "u32 id", "display_name": "ID"}, {"def": "u32 b", "sort_id": -1}]}"#;
+        assert!(matches!(deserialize_def_json(input), Err(JsonError::Expected { path, .. }) if path == "fields[1].sort_id"));
+        assert!(matches!(deserialize_def_json("{"), Err(JsonError::Syntax(_))));
+    }
+
+    #[test]
+    fn keep_raw() {
+        let input = TEST_DEF.replacen(r#"<Field Def="u8 isEnable:1">"#, r#"<Field Def="u8 isEnable:1" Tool="x"><Custom> kept </Custom>"#, 1);
+        let paramdef = deserialize_def(&input).expect("deserializes");
+        assert!(paramdef.fields.iter().all(|a| a.raw.is_empty()));
+
+        let opts = DeserializeOptions { keep_raw: true, ..Default::default() };
+        let paramdef = deserialize_def_with_options(&input, &opts).expect("deserializes");
+        let raw = &paramdef.fields[1].raw;
+        assert_eq!(raw.get("@Def").map(String::as_str), Some("u8 isEnable:1"));
+        assert_eq!(raw.get("@Tool").map(String::as_str), Some("x"));
+        assert_eq!(raw.get("Custom").map(String::as_str), Some(" kept "));
+        assert_eq!(raw.get("DisplayName"), paramdef.fields[1].display_name.as_ref());
+    }
+
+    #[test]
+    fn field_missing_def() {
+        let input = TEST_DEF.replacen(r#"<Field Def="dummy8 pad:7" />"#, "<!-- Padding -->\n<Field />", 1);
+        assert!(matches!(deserialize_def(input), Err(ParamdefDeserializeError::FieldMissingDef { index: 2 })));
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let input = format!("\u{feff}{}", TEST_DEF);
+        assert_eq!(deserialize_def(&input).expect("deserializes"), deserialize_def(TEST_DEF).expect("deserializes"));
+        let from_reader = deserialize_def_from_reader(input.as_bytes()).expect("deserializes");
+        assert_eq!(from_reader, deserialize_def(TEST_DEF).expect("deserializes"));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn shift_jis_input() {
+        let input = TEST_DEF.replacen("utf-8", "shift_jis", 1).replacen("Reference ID", "参照ID", 1);
+        let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode(&input);
+        let paramdef = deserialize_def_from_reader(&*encoded).expect("deserializes");
+        assert_eq!(paramdef.fields[0].display_name.as_deref(), Some("参照ID"));
+    }
+
+    #[test]
+    fn dtd_option() {
+        let input = TEST_DEF.replacen("<PARAMDEF", "<!DOCTYPE PARAMDEF []>\n<PARAMDEF", 1);
+        assert!(matches!(deserialize_def(&input), Err(ParamdefDeserializeError::XmlParsing(_))));
+
+        let opts = DeserializeOptions { xml: XmlOptions { allow_dtd: true }, ..Default::default() };
+        let paramdef = deserialize_def_with_options(&input, &opts).expect("deserializes");
+        assert_eq!(paramdef, deserialize_def(TEST_DEF).expect("deserializes"));
+    }
+
+    #[test]
+    fn blank_element_position() {
+        let err = deserialize_def("<PARAMDEF>\n  <ParamType>A</ParamType>\n  <DataVersion/>\n</PARAMDEF>").expect_err("fails");
+        assert_eq!(err.position(), Some(TextPos::new(3, 3)));
+        assert_eq!(err.to_string(), "XML blank element: DataVersion at 3:3");
+    }
+
+    #[test]
+    fn missing_element_named() {
+        let err = deserialize_def("<PARAMDEF>\n  <DataVersion>1</DataVersion>\n</PARAMDEF>").expect_err("fails");
+        assert_eq!(err.position(), None);
+        assert_eq!(err.to_string(), "Missing required element: ParamType");
+        let err = deserialize_def("<PARAM>\n  <ParamType>A</ParamType>\n</PARAM>").expect_err("fails");
+        assert_eq!(err.to_string(), "Missing required element: PARAMDEF");
+    }
+
+    #[test]
+    fn blank_unknown_element() {
+        let input = TEST_DEF.replace("<FormatVersion>", "<Generator/>\n  <FormatVersion>");
+        assert!(deserialize_def(input).expect("deserializes") == deserialize_def(TEST_DEF).expect("deserializes"));
+    }
+
+    #[test]
+    fn warnings() {
+        let (_, warnings) = deserialize_def_with_warnings(TEST_DEF).expect("deserializes");
+        assert!(warnings.is_empty());
+
+        let input = TEST_DEF
+            .replace("<FormatVersion>", "<Generator/>\n  <FormatVersion>")
+            .replace("<EditFlags>Wrap, Lock</EditFlags>", "<EditFlags>Wrap, Loop</EditFlags><UnkB8>0</UnkB8>")
+            .replace("<DisplayFormat>%d</DisplayFormat>", "<DisplayFormat>%q</DisplayFormat>");
+        let (_, warnings) = deserialize_def_with_warnings(input).expect("deserializes");
+        assert_eq!(warnings, vec![
+            DeserializeWarning::UnknownRootElement { element: "Generator".into() },
+            DeserializeWarning::InvalidDisplayFormat { field: "refId".into(), format: "%q".into() },
+            DeserializeWarning::UnknownFieldElement { field: "isEnable".into(), element: "UnkB8".into() },
+            DeserializeWarning::UnknownEditFlag { field: "isEnable".into(), flag: "Loop".into() },
+        ]);
+    }
+
+    #[test]
+    fn param_type_trimmed() {
+        let input = TEST_DEF.replace("<ParamType>TEST_PARAM_ST</ParamType>", "<ParamType> TEST_PARAM_ST\n </ParamType>");
+        assert_eq!(deserialize_def(input).expect("deserializes").param_type, "TEST_PARAM_ST");
+
+        let input = TEST_DEF.replace("<ParamType>TEST_PARAM_ST</ParamType>", "<ParamType>  </ParamType>");
+        assert!(matches!(deserialize_def(input), Err(ParamdefDeserializeError::MissingParamData(_))));
+    }
+
+    #[test]
+    fn text_trimmed() {
+        let input = TEST_DEF
+            .replace("<DataVersion>2</DataVersion>", "<DataVersion>\n    2\n  </DataVersion>")
+            .replace("<Minimum>-1</Minimum>", "<Minimum> -1 </Minimum>")
+            .replace("<SortID>100</SortID>", "<SortID>\n        100\n      </SortID>")
+            .replace("<DisplayName>Enabled</DisplayName>", "<DisplayName>\n        Enabled\n      </DisplayName>")
+            .replace("<Description>ID of the referenced param</Description>", "<Description>  </Description>");
+        let def = deserialize_def(input).expect("deserializes");
+        assert_eq!(def.data_version, 2);
+        assert_eq!(def.fields[0].minimum, Some(-1.0));
+        assert_eq!(def.fields[0].sort_id, Some(100));
+        assert_eq!(def.fields[0].description, None);
+        assert_eq!(def.fields[1].display_name.as_deref(), Some("Enabled"));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::binary::{BitPosition, FieldOffset};
+use crate::{ParamDef, ParamField, ParamFieldDef, ParamFieldType};
+
+/// A difference in a single field between two [ParamDef]s, found by [`ParamDef::diff`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FieldChange<'a> {
+    /// The field only exists in the newer def
+    Added {
+        name: &'a str,
+    },
+
+    /// The field only exists in the older def
+    Removed {
+        name: &'a str,
+    },
+
+    /// The field's type changed. Bitfields that keep their storage kind report
+    /// [`FieldChange::BitsChanged`] instead.
+    TypeChanged {
+        name: &'a str,
+        old: &'a ParamFieldType,
+        new: &'a ParamFieldType,
+    },
+
+    /// The field, or for bitfields the storage unit holding it, starts at a different byte offset
+    Moved {
+        name: &'a str,
+        old: usize,
+        new: usize,
+    },
+
+    /// The bitfield's width or position within its storage unit changed
+    BitsChanged {
+        name: &'a str,
+        old: BitPosition,
+        new: BitPosition,
+    },
+
+    /// The field's array length changed, or it became or stopped being an array
+    ArrayLenChanged {
+        name: &'a str,
+        old: Option<usize>,
+        new: Option<usize>,
+    },
+}
+
+/// Differences in the human readable metadata of two [ParamField]s, found by
+/// [`ParamField::metadata_diff`]. Members are `None` or empty where the fields agree.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct FieldMetaDiff<'a> {
+    pub display_name: Option<MetaChange<'a>>,
+    pub description: Option<MetaChange<'a>>,
+
+    /// Localized descriptions that differ, by language tag in sorted order
+    pub descriptions: Vec<(&'a str, MetaChange<'a>)>,
+}
+
+/// A metadata value that differs between two fields, as declared by each. `None` when the field
+/// doesn't declare it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MetaChange<'a> {
+    pub this: Option<&'a str>,
+    pub other: Option<&'a str>,
+}
+
+impl FieldMetaDiff<'_> {
+    /// Whether the fields' metadata is the same
+    pub fn is_empty(&self) -> bool {
+        self.display_name.is_none() && self.description.is_none() && self.descriptions.is_empty()
+    }
+}
+
+impl MetaChange<'_> {
+    /// Whether only one of the fields declares the value, such as a missing translation
+    pub fn is_missing(&self) -> bool {
+        self.this.is_some() != self.other.is_some()
+    }
+}
+
+impl ParamField {
+    /// Compares the display name, description and localized descriptions of this field against
+    /// `other`, such as the same field in a def for another language. The type, layout and
+    /// other metadata are not compared.
+    pub fn metadata_diff<'a>(&'a self, other: &'a ParamField) -> FieldMetaDiff<'a> {
+        let change = |this: Option<&'a String>, other: Option<&'a String>| {
+            (this != other).then(|| MetaChange { this: this.map(String::as_str), other: other.map(String::as_str) })
+        };
+        let mut languages: Vec<&str> = self.descriptions.keys().chain(other.descriptions.keys()).map(String::as_str).collect();
+        languages.sort_unstable();
+        languages.dedup();
+
+        FieldMetaDiff {
+            display_name: change(self.display_name.as_ref(), other.display_name.as_ref()),
+            description: change(self.description.as_ref(), other.description.as_ref()),
+            descriptions: languages.into_iter()
+                .filter_map(|language| Some((language, change(self.descriptions.get(language), other.descriptions.get(language))?)))
+                .collect(),
+        }
+    }
+}
+
+impl ParamDef {
+    /// Compares the layout of this def against a newer version, field by field.
+    ///
+    /// Fields are matched by internal name; the first field with a name is used when a name is
+    /// duplicated. Changes are listed in this def's field order, followed by added fields in the
+    /// newer def's order. Bitfields are compared individually, so widening one flag reports that
+    /// flag and any flags it shifted, rather than the whole storage unit. Metadata such as
+    /// descriptions is not compared.
+    pub fn diff<'a>(&'a self, newer: &'a ParamDef) -> Vec<FieldChange<'a>> {
+        let old_offsets = self.field_offsets();
+        let new_offsets = newer.field_offsets();
+        let find = |def: &ParamDef, name: &str| def.fields.iter().position(|a| a.field_def.name == name);
+        let mut changes = Vec::new();
+
+        for (field, old_offset) in self.fields.iter().zip(&old_offsets) {
+            let name = field.field_def.name.as_str();
+            match find(newer, name) {
+                Some(index) => {
+                    let new_field = &newer.fields[index];
+                    diff_field(name, &field.field_def, old_offset, &new_field.field_def, &new_offsets[index], &mut changes);
+                }
+                None => changes.push(FieldChange::Removed { name }),
+            }
+        }
+
+        for field in &newer.fields {
+            let name = field.field_def.name.as_str();
+            if find(self, name).is_none() {
+                changes.push(FieldChange::Added { name });
+            }
+        }
+        changes
+    }
+}
+
+fn diff_field<'a>(name: &'a str, old_def: &'a ParamFieldDef, old_offset: &FieldOffset, new_def: &'a ParamFieldDef,
+                  new_offset: &FieldOffset, changes: &mut Vec<FieldChange<'a>>) {
+    let (old, new) = (&old_def.field_type, &new_def.field_type);
+    let same_storage = matches!((old.bit_storage(), new.bit_storage()), (Some((a, _)), Some((b, _))) if a == b);
+    if old != new && !same_storage {
+        changes.push(FieldChange::TypeChanged { name, old, new });
+    }
+    if old_def.array_len != new_def.array_len {
+        changes.push(FieldChange::ArrayLenChanged { name, old: old_def.array_len, new: new_def.array_len });
+    }
+    if old_offset.byte_offset != new_offset.byte_offset {
+        changes.push(FieldChange::Moved { name, old: old_offset.byte_offset, new: new_offset.byte_offset });
+    }
+    if let (true, Some(old_bits), Some(new_bits)) = (same_storage, old_offset.bits, new_offset.bits) {
+        if old_bits != new_bits {
+            changes.push(FieldChange::BitsChanged { name, old: old_bits, new: new_bits });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::BitPosition;
+    use crate::diff::{FieldChange, MetaChange};
+    use crate::ParamFieldType;
+    use crate::test_util::{field, paramdef};
+
+    #[test]
+    fn metadata_diff() {
+        let mut english = field("u8 isEnable:1");
+        english.display_name = Some("Enabled".into());
+        english.description = Some("Whether enabled".into());
+        english.descriptions.insert("enUS".into(), "Whether enabled".into());
+        english.descriptions.insert("frFR".into(), "Activé ou non".into());
+        let mut japanese = field("f32 renamed");
+        japanese.display_name = Some("有効".into());
+        japanese.description = Some("Whether enabled".into());
+        japanese.descriptions.insert("enUS".into(), "Whether enabled".into());
+        japanese.descriptions.insert("jaJP".into(), "有効かどうか".into());
+
+        let diff = english.metadata_diff(&japanese);
+        assert_eq!(diff.display_name, Some(MetaChange { this: Some("Enabled"), other: Some("有効") }));
+        assert_eq!(diff.description, None);
+        assert_eq!(diff.descriptions, vec![
+            ("frFR", MetaChange { this: Some("Activé ou non"), other: None }),
+            ("jaJP", MetaChange { this: None, other: Some("有効かどうか") }),
+        ]);
+        assert!(diff.descriptions.iter().all(|(_, change)| change.is_missing()));
+        assert!(!diff.display_name.expect("differs").is_missing());
+        assert!(english.metadata_diff(&english).is_empty());
+    }
+
+    #[test]
+    fn bit_width_change() {
+        let old = paramdef(&["u32 id", "u8 a:1", "u8 b:1", "u8 c:1", "u8 pad:5", "f32 rate"]);
+        let new = paramdef(&["u32 id", "u8 a:1", "u8 b:2", "u8 c:1", "u8 pad:4", "f32 rate"]);
+        assert_eq!(old.diff(&new), vec![
+            FieldChange::BitsChanged { name: "b", old: BitPosition { offset: 1, size: 1 }, new: BitPosition { offset: 1, size: 2 } },
+            FieldChange::BitsChanged { name: "c", old: BitPosition { offset: 2, size: 1 }, new: BitPosition { offset: 3, size: 1 } },
+            FieldChange::BitsChanged { name: "pad", old: BitPosition { offset: 3, size: 5 }, new: BitPosition { offset: 4, size: 4 } },
+        ]);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn added_removed_and_retyped() {
+        let old = paramdef(&["u32 id", "u16 a", "u8 gone"]);
+        let new = paramdef(&["u32 id", "u8 added", "u32 a"]);
+        assert_eq!(old.diff(&new), vec![
+            FieldChange::TypeChanged {
+                name: "a",
+                old: &ParamFieldType::u16 { bit_size: None },
+                new: &ParamFieldType::u32 { bit_size: None },
+            },
+            FieldChange::Moved { name: "a", old: 4, new: 5 },
+            FieldChange::Removed { name: "gone" },
+            FieldChange::Added { name: "added" },
+        ]);
+    }
+
+    #[test]
+    fn array_resized() {
+        let old = paramdef(&["u32 id", "u8 a[4]"]);
+        let new = paramdef(&["u32 id", "u8 a[8]"]);
+        assert_eq!(old.diff(&new), vec![FieldChange::ArrayLenChanged { name: "a", old: Some(4), new: Some(8) }]);
+        let new = paramdef(&["u32 id", "u8 a"]);
+        assert_eq!(old.diff(&new), vec![FieldChange::ArrayLenChanged { name: "a", old: Some(4), new: None }]);
+    }
+}
@@ -0,0 +1,364 @@
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+use thiserror::Error;
+use crate::binary::FieldValue;
+use crate::{ParamDef, ParamField};
+
+/// Conversion specifiers accepted in a display format
+const CONVERSIONS: &str = "diuoxXfFeEgGaAcs";
+
+/// Flag characters accepted before the width
+const FLAGS: &str = "-+ #0";
+
+/// Length modifiers, which are accepted but carry no meaning for display
+const LENGTH_MODIFIERS: &str = "hlLqjzt";
+
+/// The largest width or precision accepted, as formatting pads out to them in memory
+const MAX_WIDTH: usize = 4096;
+
+/// A parsed printf(3) style display format, such as `%0.2f` or `HP: %d%%`
+///
+/// A format holds a single conversion specification, with optional literal text around it.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DisplayFormat {
+    /// Literal text before the conversion, with `%%` unescaped to `%`
+    pub prefix: String,
+
+    /// Flag characters, such as `0` or `-`, in the order declared
+    pub flags: String,
+
+    /// Minimum field width, at most 4096 when parsed
+    pub width: Option<usize>,
+
+    /// Precision, such as the number of decimal places for floating point conversions. At most
+    /// 4096 when parsed.
+    pub precision: Option<usize>,
+
+    /// The conversion character, such as `d` or `f`
+    pub conversion: char,
+
+    /// Literal text after the conversion, with `%%` unescaped to `%`. Any further conversion
+    /// specifications are kept here as literal text, as there is only one value to format.
+    pub suffix: String,
+}
+
+/// An error when parsing a [DisplayFormat]
+#[derive(Error, Clone, Eq, PartialEq, Debug)]
+pub enum DisplayFormatError {
+    #[error("Display format has no conversion specification")]
+    MissingSpecification,
+
+    #[error("Display format has no conversion character")]
+    MissingConversion,
+
+    #[error("Unknown conversion {0:?} in display format")]
+    UnknownConversion(char),
+
+    #[error("Width or precision in display format is larger than {MAX_WIDTH}")]
+    TooLarge,
+}
+
+impl FromStr for DisplayFormat {
+    type Err = DisplayFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars().peekable();
+
+        let mut prefix = String::new();
+        loop {
+            match chars.next() {
+                Some('%') if chars.next_if_eq(&'%').is_some() => prefix.push('%'),
+                Some('%') => break,
+                Some(c) => prefix.push(c),
+                None => return Err(DisplayFormatError::MissingSpecification),
+            }
+        }
+
+        let mut flags = String::new();
+        while let Some(flag) = chars.next_if(|a| FLAGS.contains(*a)) {
+            flags.push(flag);
+        }
+
+        let width = take_number(&mut chars)?;
+        // A lone `.` means a precision of zero
+        let precision = match chars.next_if_eq(&'.') {
+            Some(_) => Some(take_number(&mut chars)?.unwrap_or(0)),
+            None => None,
+        };
+        while chars.next_if(|a| LENGTH_MODIFIERS.contains(*a)).is_some() {}
+
+        let conversion = chars.next().ok_or(DisplayFormatError::MissingConversion)?;
+        if !CONVERSIONS.contains(conversion) {
+            return Err(DisplayFormatError::UnknownConversion(conversion));
+        }
+
+        let suffix = unescape(&chars.collect::<String>());
+
+        Ok(DisplayFormat { prefix, flags, width, precision, conversion, suffix })
+    }
+}
+
+impl DisplayFormat {
+    /// Formats a value as printf(3) would, surrounded by the literal prefix and suffix.
+    ///
+    /// Integer conversions truncate floating point values, and floating point conversions accept
+    /// integers. `%s` formats any value, as does any conversion given a string value. Padding
+    /// values are always shown in their plain form.
+    pub fn format(&self, value: &FieldValue) -> String {
+        let body = match (self.conversion, number(value)) {
+            ('d' | 'i', Some(number)) => self.signed(match number {
+                Number::Signed { value, .. } => value.to_string(),
+                Number::Unsigned(value) => value.to_string(),
+                Number::Float(value) => (value.trunc() as i64).to_string(),
+            }),
+            ('u', Some(number)) => number.unsigned_bits().to_string(),
+            ('x', Some(number)) => self.alternate("0x", format!("{:x}", number.unsigned_bits())),
+            ('X', Some(number)) => self.alternate("0X", format!("{:X}", number.unsigned_bits())),
+            ('o', Some(number)) => self.alternate("0", format!("{:o}", number.unsigned_bits())),
+            ('c', Some(number)) => char::from_u32(number.unsigned_bits() as u32).map(String::from).unwrap_or_default(),
+            ('f' | 'F', Some(number)) => {
+                self.signed(format!("{:.*}", self.precision.unwrap_or(6), number.float()))
+            }
+            ('e', Some(number)) => self.signed(format!("{:.*e}", self.precision.unwrap_or(6), number.float())),
+            ('E', Some(number)) => self.signed(format!("{:.*E}", self.precision.unwrap_or(6), number.float())),
+            ('g' | 'G' | 'a' | 'A', Some(number)) => self.signed(number.float().to_string()),
+            (_, _) => {
+                let text = value.to_string();
+                match (self.conversion, self.precision) {
+                    ('s', Some(precision)) => text.chars().take(precision).collect(),
+                    _ => text,
+                }
+            }
+        };
+        format!("{}{}{}", self.prefix, self.pad(body), self.suffix)
+    }
+
+    fn has_flag(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// Applies the `+` and space flags to a formatted number
+    fn signed(&self, number: String) -> String {
+        match number.starts_with('-') {
+            false if self.has_flag('+') => format!("+{}", number),
+            false if self.has_flag(' ') => format!(" {}", number),
+            _ => number,
+        }
+    }
+
+    /// Applies the `#` flag to a formatted number
+    fn alternate(&self, prefix: &str, number: String) -> String {
+        match self.has_flag('#') && number != "0" {
+            true => format!("{}{}", prefix, number),
+            false => number,
+        }
+    }
+
+    /// Pads to the minimum width, honoring the `-` and `0` flags
+    fn pad(&self, body: String) -> String {
+        let width = self.width.unwrap_or(0);
+        let len = body.chars().count();
+        if len >= width {
+            return body;
+        }
+        let padding = width - len;
+        if self.has_flag('-') {
+            format!("{}{}", body, " ".repeat(padding))
+        } else if self.has_flag('0') && !matches!(self.conversion, 's' | 'c') {
+            let sign_len = body.find(|a: char| !matches!(a, '-' | '+' | ' ')).unwrap_or(0);
+            let (sign, digits) = body.split_at(sign_len);
+            format!("{}{}{}", sign, "0".repeat(padding), digits)
+        } else {
+            format!("{}{}", " ".repeat(padding), body)
+        }
+    }
+}
+
+impl ParamField {
+    /// Formats a value of this field for display.
+    ///
+    /// The parsed [`ParamField::display_format`] is used when present. A declared format without
+    /// a usable conversion specification is treated as literal text, and the value is appended
+    /// to it in its plain form. Without a declared format, the value is shown in its plain form.
+    pub fn format_value(&self, value: &FieldValue) -> String {
+        match (&self.display_format, &self.printf_format) {
+            (Some(display_format), _) => display_format.format(value),
+            (None, Some(literal)) => format!("{}{}", unescape(literal), value),
+            (None, None) => value.to_string(),
+        }
+    }
+}
+
+/// A field bundled with what an editor needs to render it, borrowed from the [ParamDef].
+/// See [`ParamDef::render_fields`].
+#[derive(Copy, Clone, Debug)]
+pub struct RenderField<'a> {
+    /// The field being rendered
+    pub field: &'a ParamField,
+
+    /// See [`ParamField::effective_display_name`]
+    pub display_name: &'a str,
+
+    /// The parsed display format, if the field declares a usable one
+    pub display_format: Option<&'a DisplayFormat>,
+
+    /// See [`ParamField::effective_range`]
+    pub value_range: Option<(f64, f64)>,
+}
+
+impl ParamDef {
+    /// Iterates over the fields in declaration order, each bundled with its display name,
+    /// display format and value range.
+    pub fn render_fields(&self) -> impl Iterator<Item = RenderField<'_>> {
+        self.fields.iter().map(|field| RenderField {
+            field,
+            display_name: field.effective_display_name(),
+            display_format: field.display_format.as_ref(),
+            value_range: field.effective_range(),
+        })
+    }
+}
+
+enum Number {
+    Signed {
+        value: i64,
+        /// Width of the source type, for reinterpreting as unsigned
+        bits: u32,
+    },
+    Unsigned(u64),
+    Float(f64),
+}
+
+impl Number {
+    fn unsigned_bits(&self) -> u64 {
+        match *self {
+            Number::Signed { value, bits } => value as u64 & (u64::MAX >> (64 - bits)),
+            Number::Unsigned(value) => value,
+            Number::Float(value) => value.trunc() as u64,
+        }
+    }
+
+    fn float(&self) -> f64 {
+        match *self {
+            Number::Signed { value, .. } => value as f64,
+            Number::Unsigned(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+}
+
+fn number(value: &FieldValue) -> Option<Number> {
+    Some(match *value {
+        FieldValue::S8(value) => Number::Signed { value: value as i64, bits: 8 },
+        FieldValue::S16(value) => Number::Signed { value: value as i64, bits: 16 },
+        FieldValue::S32(value) => Number::Signed { value: value as i64, bits: 32 },
+        FieldValue::U8(value) => Number::Unsigned(value as u64),
+        FieldValue::U16(value) => Number::Unsigned(value as u64),
+        FieldValue::U32(value) => Number::Unsigned(value as u64),
+        FieldValue::B32(value) => Number::Unsigned(value as u64),
+        FieldValue::F32(value) | FieldValue::A32(value) => Number::Float(value as f64),
+        FieldValue::F64(value) => Number::Float(value),
+        FieldValue::Fixstr(_) | FieldValue::FixstrW(_) | FieldValue::Dummy8(_) | FieldValue::Array(_) => return None,
+    })
+}
+
+/// Takes a width or precision, if any, failing if it is larger than [MAX_WIDTH]
+fn take_number(chars: &mut Peekable<Chars>) -> Result<Option<usize>, DisplayFormatError> {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    match digits.is_empty() {
+        true => Ok(None),
+        false => usize::from_str(&digits).ok().filter(|a| *a <= MAX_WIDTH).map(Some).ok_or(DisplayFormatError::TooLarge),
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("%%", "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::binary::FieldValue;
+    use crate::display_format::{DisplayFormat, DisplayFormatError};
+    use crate::test_util::{field, paramdef};
+
+    fn format(flags: &str, width: Option<usize>, precision: Option<usize>, conversion: char) -> DisplayFormat {
+        DisplayFormat { prefix: String::new(), flags: flags.into(), width, precision, conversion, suffix: String::new() }
+    }
+
+    fn apply(format: &str, value: FieldValue) -> String {
+        DisplayFormat::from_str(format).expect("parses").format(&value)
+    }
+
+    #[test]
+    fn common_formats() {
+        assert_eq!(DisplayFormat::from_str("%d"), Ok(format("", None, None, 'd')));
+        assert_eq!(DisplayFormat::from_str("%u"), Ok(format("", None, None, 'u')));
+        assert_eq!(DisplayFormat::from_str("%x"), Ok(format("", None, None, 'x')));
+        assert_eq!(DisplayFormat::from_str("%i"), Ok(format("", None, None, 'i')));
+        assert_eq!(DisplayFormat::from_str("%0.2f"), Ok(format("0", None, Some(2), 'f')));
+        assert_eq!(DisplayFormat::from_str("%-8.3lf"), Ok(format("-", Some(8), Some(3), 'f')));
+    }
+
+    #[test]
+    fn literal_text() {
+        let parsed = DisplayFormat::from_str("100%% HP: %d%% %d").expect("parses");
+        assert_eq!(parsed.prefix, "100% HP: ");
+        assert_eq!(parsed.conversion, 'd');
+        assert_eq!(parsed.suffix, "% %d");
+    }
+
+    #[test]
+    fn invalid_formats() {
+        assert_eq!(DisplayFormat::from_str("d"), Err(DisplayFormatError::MissingSpecification));
+        assert_eq!(DisplayFormat::from_str("%%"), Err(DisplayFormatError::MissingSpecification));
+        assert_eq!(DisplayFormat::from_str("%0.2"), Err(DisplayFormatError::MissingConversion));
+        assert_eq!(DisplayFormat::from_str("%y"), Err(DisplayFormatError::UnknownConversion('y')));
+        assert_eq!(DisplayFormat::from_str("%999999999999d"), Err(DisplayFormatError::TooLarge));
+        assert_eq!(DisplayFormat::from_str("%.999999999999f"), Err(DisplayFormatError::TooLarge));
+        assert_eq!(DisplayFormat::from_str("%99999999999999999999999d"), Err(DisplayFormatError::TooLarge));
+        assert_eq!(DisplayFormat::from_str("%4097d"), Err(DisplayFormatError::TooLarge));
+        assert!(DisplayFormat::from_str("%4096.4096f").is_ok());
+    }
+
+    #[test]
+    fn format_values() {
+        assert_eq!(apply("%d%%", FieldValue::S32(50)), "50%");
+        assert_eq!(apply("x%d", FieldValue::U8(3)), "x3");
+        assert_eq!(apply("HP: %d", FieldValue::F32(99.9)), "HP: 99");
+        assert_eq!(apply("%0.2f", FieldValue::F32(1.5)), "1.50");
+        assert_eq!(apply("%05d", FieldValue::S16(-42)), "-0042");
+        assert_eq!(apply("%x", FieldValue::S8(-1)), "ff");
+        assert_eq!(apply("%#X", FieldValue::U32(255)), "0XFF");
+        assert_eq!(apply("%-4u|", FieldValue::U16(7)), "7   |");
+        assert_eq!(apply("%+.1f", FieldValue::F64(2.0)), "+2.0");
+        assert_eq!(apply("%d", FieldValue::FixstrW("text".into())), "text");
+    }
+
+    #[test]
+    fn format_value_without_specification() {
+        let mut without_specification = field("u8 a");
+        without_specification.printf_format = Some("100%%".into());
+        assert_eq!(without_specification.format_value(&FieldValue::U8(5)), "100%5");
+
+        assert_eq!(field("u8 a").format_value(&FieldValue::U8(5)), "5");
+    }
+
+    #[test]
+    fn render_fields() {
+        let mut def = paramdef(&["u8 a", "f32 b", "fixstr c[4]"]);
+        def.fields[0].display_name = Some("First".into());
+        def.fields[0].maximum = Some(100.0);
+        def.fields[1].display_format = DisplayFormat::from_str("%0.2f").ok();
+
+        let rendered: Vec<_> = def.render_fields().collect();
+        assert_eq!(rendered.iter().map(|a| a.display_name).collect::<Vec<_>>(), vec!["First", "b", "c"]);
+        assert_eq!(rendered[0].value_range, Some((0.0, 100.0)));
+        assert_eq!(rendered[1].display_format, Some(&format("0", None, Some(2), 'f')));
+        assert_eq!(rendered[2].value_range, None);
+        assert!(std::ptr::eq(rendered[2].field, &def.fields[2]));
+    }
+}
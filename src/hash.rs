@@ -0,0 +1,303 @@
+use std::hash::{Hash, Hasher};
+use crate::{DummyType, ParamDef, ParamField, ParamFieldDef, ParamFieldType};
+
+/// Floats are compared and hashed by their bit representation, so that `NaN` defaults
+/// are equal to themselves and equality stays consistent with hashing.
+fn float_bits(value: Option<f64>) -> Option<u64> {
+    value.map(f64::to_bits)
+}
+
+impl PartialEq for ParamFieldDef {
+    fn eq(&self, other: &Self) -> bool {
+        self.field_type == other.field_type
+            && self.name == other.name
+            && float_bits(self.default_value) == float_bits(other.default_value)
+            && self.array_len == other.array_len
+            && self.comment == other.comment
+    }
+}
+
+impl Eq for ParamFieldDef {}
+
+impl Hash for ParamFieldDef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.field_type.hash(state);
+        self.name.hash(state);
+        float_bits(self.default_value).hash(state);
+        self.array_len.hash(state);
+        self.comment.hash(state);
+    }
+}
+
+impl PartialEq for ParamField {
+    fn eq(&self, other: &Self) -> bool {
+        self.field_def == other.field_def
+            && self.display_name == other.display_name
+            && self.enum_tdf == other.enum_tdf
+            && self.description == other.description
+            && self.descriptions == other.descriptions
+            && self.printf_format == other.printf_format
+            && self.display_format == other.display_format
+            && self.edit_flags == other.edit_flags
+            && float_bits(self.minimum) == float_bits(other.minimum)
+            && float_bits(self.maximum) == float_bits(other.maximum)
+            && float_bits(self.increment) == float_bits(other.increment)
+            && self.sort_id == other.sort_id
+            && self.raw == other.raw
+    }
+}
+
+impl Eq for ParamField {}
+
+impl Hash for ParamField {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.field_def.hash(state);
+        self.display_name.hash(state);
+        self.enum_tdf.hash(state);
+        self.description.hash(state);
+        // Hashed in sorted order, as the map's iteration order isn't stable
+        let mut descriptions: Vec<(&String, &String)> = self.descriptions.iter().collect();
+        descriptions.sort_unstable();
+        descriptions.hash(state);
+        self.printf_format.hash(state);
+        self.display_format.hash(state);
+        self.edit_flags.hash(state);
+        float_bits(self.minimum).hash(state);
+        float_bits(self.maximum).hash(state);
+        float_bits(self.increment).hash(state);
+        self.sort_id.hash(state);
+        let mut raw: Vec<(&String, &String)> = self.raw.iter().collect();
+        raw.sort_unstable();
+        raw.hash(state);
+    }
+}
+
+impl ParamDef {
+    /// A hash of the entire contents of the def.
+    ///
+    /// Unlike hashing with [`std::collections::hash_map::DefaultHasher`], this uses a fixed
+    /// algorithm (64-bit FNV-1a) with integers hashed as little endian and lengths as 64 bits,
+    /// so it is the same across runs and platforms and can be persisted to detect whether a
+    /// reloaded def has changed. Strings and collections are hashed through the standard
+    /// library's [`Hash`] implementations, which may change between Rust versions, so a hash
+    /// persisted by a build with another compiler may differ for an unchanged def.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A hash of only the binary layout of the def, the same across runs, platforms and Rust
+    /// versions.
+    ///
+    /// The fingerprint covers the number of fields and, in declaration order, each field's
+    /// [`ParamFieldType`] including its bit size, string length or dummy
+    /// length, and its array length. Everything else is ignored: the param type, versions,
+    /// endianness, string format, field names, default values and all editor metadata such as
+    /// descriptions.
+    ///
+    /// Two defs that describe identical rows fingerprint equal even if one is better documented.
+    pub fn layout_fingerprint(&self) -> u64 {
+        // Values are written explicitly rather than through derived Hash implementations, whose
+        // output isn't guaranteed to stay the same
+        let mut hasher = StableHasher::new();
+        let write_option = |hasher: &mut StableHasher, value: Option<u64>| match value {
+            Some(value) => {
+                hasher.write_u8(1);
+                hasher.write_u64(value);
+            }
+            None => hasher.write_u8(0),
+        };
+        hasher.write_u64(self.fields.len() as u64);
+        for field in &self.fields {
+            let (tag, payload) = match field.field_def.field_type {
+                ParamFieldType::s8 { bit_size } => (0, bit_size.map(u64::from)),
+                ParamFieldType::u8 { bit_size } => (1, bit_size.map(u64::from)),
+                ParamFieldType::s16 { bit_size } => (2, bit_size.map(u64::from)),
+                ParamFieldType::u16 { bit_size } => (3, bit_size.map(u64::from)),
+                ParamFieldType::s32 { bit_size } => (4, bit_size.map(u64::from)),
+                ParamFieldType::u32 { bit_size } => (5, bit_size.map(u64::from)),
+                ParamFieldType::b32 => (6, None),
+                ParamFieldType::f32 => (7, None),
+                ParamFieldType::a32 => (8, None),
+                ParamFieldType::f64 => (9, None),
+                ParamFieldType::fixstr { length } => (10, Some(length as u64)),
+                ParamFieldType::fixstrW { length } => (11, Some(length as u64)),
+                ParamFieldType::dummy8 { length: None } => (12, None),
+                ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => (13, Some(length as u64)),
+                ParamFieldType::dummy8 { length: Some(DummyType::Bits(bits)) } => (14, Some(bits as u64)),
+            };
+            hasher.write_u8(tag);
+            write_option(&mut hasher, payload);
+            write_option(&mut hasher, field.field_def.array_len.map(|a| a as u64));
+        }
+        hasher.finish()
+    }
+
+    /// Whether two defs describe rows that are read the same way.
+    ///
+    /// Compares the endianness, the string format, and in declaration order each field's
+    /// [`ParamFieldType`] including its bit size, string length or dummy
+    /// length, and its array length. Everything else is ignored: the param type, versions, field
+    /// names, default values and all editor metadata such as display names, descriptions and
+    /// ranges.
+    ///
+    /// Unlike [`ParamDef::layout_fingerprint`], the endianness and string format are compared,
+    /// as they change how the same bytes decode.
+    pub fn structurally_eq(&self, other: &ParamDef) -> bool {
+        self.endian == other.endian
+            && self.string_format == other.string_format
+            && self.fields.len() == other.fields.len()
+            && self.fields.iter().zip(&other.fields)
+                .all(|(a, b)| a.field_def.field_type == b.field_def.field_type && a.field_def.array_len == b.field_def.array_len)
+    }
+}
+
+/// 64-bit FNV-1a, used where hashes must be reproducible across runs. Integers are hashed as
+/// little endian bytes, with `usize` and `isize` widened to 64 bits, so that hashes are also the
+/// same on every platform.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub fn new() -> StableHasher {
+        StableHasher(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+    use crate::ParamdefEndian;
+    use crate::hash::StableHasher;
+    use crate::test_util::paramdef;
+
+    #[test]
+    fn content_hash_tracks_changes() {
+        let def = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        let same = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        assert!(def == same);
+        assert_eq!(def.content_hash(), same.content_hash());
+
+        let mut changed = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        changed.fields[1].description = Some("Changed".into());
+        assert!(def != changed);
+        assert_ne!(def.content_hash(), changed.content_hash());
+    }
+
+    #[test]
+    fn stable_hasher_is_platform_independent() {
+        let hash = |write: &dyn Fn(&mut StableHasher)| {
+            let mut hasher = StableHasher::new();
+            write(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&|a| a.write_usize(0x0102)), hash(&|a| a.write(&[2, 1, 0, 0, 0, 0, 0, 0])));
+        assert_eq!(hash(&|a| a.write_isize(-2)), hash(&|a| a.write_u64(-2i64 as u64)));
+        assert_eq!(hash(&|a| a.write_u32(0x0102)), hash(&|a| a.write(&[2, 1, 0, 0])));
+        // FNV-1a of the byte 'a'
+        assert_eq!(hash(&|a| a.write(b"a")), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn nan_defaults_are_equal() {
+        let mut def = paramdef(&["f32 a"]);
+        def.fields[0].field_def.default_value = Some(f64::NAN);
+        let mut other = paramdef(&["f32 a"]);
+        other.fields[0].field_def.default_value = Some(f64::NAN);
+        assert!(def == other);
+    }
+
+    #[test]
+    fn layout_fingerprint_ignores_metadata() {
+        let def = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        let mut documented = paramdef(&["u32 renamed:3", "f32 b = 2", "fixstr c[8]"]);
+        documented.fields[0].description = Some("Documented".into());
+        documented.fields[2].display_name = Some("Name".into());
+        assert_eq!(def.layout_fingerprint(), documented.layout_fingerprint());
+
+        let resized = paramdef(&["u32 a:4", "f32 b = 1.5", "fixstr c[8]"]);
+        assert_ne!(def.layout_fingerprint(), resized.layout_fingerprint());
+        let reordered = paramdef(&["f32 b = 1.5", "u32 a:3", "fixstr c[8]"]);
+        assert_ne!(def.layout_fingerprint(), reordered.layout_fingerprint());
+
+        // Pinned, as fingerprints are persisted and compared across builds
+        assert_eq!(paramdef(&["u8 a"]).layout_fingerprint(), 0xa075e5e3e756c937);
+
+        let array = paramdef(&["u8 a[10]", "b32 b"]);
+        let string = paramdef(&["u8 a", "fixstr b[6]"]);
+        assert_ne!(array.layout_fingerprint(), string.layout_fingerprint());
+    }
+
+    #[test]
+    fn structurally_eq_ignores_metadata() {
+        let def = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        let mut localized = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        localized.fields[0].description = Some("Localized".into());
+        localized.fields[1].display_name = Some("Name".into());
+        localized.fields[1].maximum = Some(100.0);
+        assert!(def.structurally_eq(&localized));
+        assert!(def != localized);
+
+        let mut big_endian = paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[8]"]);
+        big_endian.endian = ParamdefEndian::Big;
+        assert!(!def.structurally_eq(&big_endian));
+        assert!(!def.structurally_eq(&paramdef(&["u32 a:3", "f32 b = 1.5", "fixstr c[9]"])));
+        assert!(!def.structurally_eq(&paramdef(&["u32 a:3", "f32 b = 1.5"])));
+    }
+}
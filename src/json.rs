@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde_json::{Map, Value};
+use thiserror::Error;
+use crate::deserialize::DefParseError;
+use crate::display_format::DisplayFormat;
+use crate::{EditFlags, EnumRef, Paramdex, ParamDef, ParamdefEndian, ParamdefFormat, ParamField, ParamFieldDef};
+
+/// An error when reading a [Paramdex] or [ParamDef] from JSON. `path` locates the offending
+/// value, such as `EQUIP_PARAM_WEAPON_ST.fields[3].def`, or `fields[3].def` for a single def.
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("Expected {expected} at {path}")]
+    Expected {
+        path: String,
+        expected: &'static str,
+    },
+
+    #[error("Invalid JSON: {0}")]
+    Syntax(#[from] serde_json::Error),
+
+    #[error("Invalid def at {path}: {source}")]
+    Def {
+        path: String,
+        source: Box<DefParseError>,
+    },
+}
+
+impl Paramdex {
+    /// Converts the Paramdex to a JSON object keyed by param type, for tools that want to dump
+    /// or exchange the structure of defs. Read it back with [`Paramdex::from_json`].
+    ///
+    /// Each def is an object with its header values and a `fields` array. The type, name,
+    /// default and comment of each field are written together as a def string under `def`, just
+    /// as in the XML, and metadata the field does not declare is omitted.
+    pub fn to_json(&self) -> Value {
+        Value::Object(self.definitions.iter().map(|(param_type, paramdef)| (param_type.clone(), paramdef_to_json(paramdef))).collect())
+    }
+
+    /// Reads a Paramdex from JSON in the form written by [`Paramdex::to_json`].
+    ///
+    /// Display formats are parsed from the `display_format` strings, and those that do not parse
+    /// are left as `None`, as when deserializing XML.
+    pub fn from_json(json: &Value) -> Result<Paramdex, JsonError> {
+        let defs = json.as_object().ok_or_else(|| expected("", "an object of defs"))?;
+        let mut paramdex = Paramdex::empty();
+        for (param_type, paramdef) in defs {
+            paramdex.definitions.insert(param_type.clone(), paramdef_from_json(paramdef, param_type)?);
+        }
+        Ok(paramdex)
+    }
+}
+
+impl ParamDef {
+    /// Converts the def to a JSON object, in the form of each def written by
+    /// [`Paramdex::to_json`]. Read it back with
+    /// [`deserialize_def_json`](crate::deserialize::deserialize_def_json).
+    pub fn to_json(&self) -> Value {
+        paramdef_to_json(self)
+    }
+}
+
+fn paramdef_to_json(paramdef: &ParamDef) -> Value {
+    let mut object = Map::new();
+    object.insert("param_type".into(), paramdef.param_type.clone().into());
+    object.insert("data_version".into(), paramdef.data_version.into());
+    object.insert("big_endian".into(), (paramdef.endian == ParamdefEndian::Big).into());
+    object.insert("unicode".into(), (paramdef.string_format == ParamdefFormat::UTF16).into());
+    object.insert("format_version".into(), paramdef.format_version.into());
+    object.insert("fields".into(), paramdef.fields.iter().map(field_to_json).collect());
+    Value::Object(object)
+}
+
+fn field_to_json(field: &ParamField) -> Value {
+    let mut object = Map::new();
+    object.insert("def".into(), field.field_def.to_string().into());
+    let mut insert = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            object.insert(key.into(), value);
+        }
+    };
+    insert("display_name", field.display_name.clone().map(Value::from));
+    insert("enum", field.enum_tdf.as_ref().map(|a| a.name().into()));
+    insert("description", field.description.clone().map(Value::from));
+    if !field.descriptions.is_empty() {
+        insert("descriptions", Some(Value::Object(field.descriptions.iter().map(|(k, v)| (k.clone(), v.clone().into())).collect())));
+    }
+    insert("display_format", field.printf_format.clone().map(Value::from));
+    insert("edit_flags", field.edit_flags.map(|a| {
+        Value::Object(Map::from_iter([("wrap".to_owned(), a.wrap.into()), ("lock".to_owned(), a.lock.into())]))
+    }));
+    insert("minimum", field.minimum.map(Value::from));
+    insert("maximum", field.maximum.map(Value::from));
+    insert("increment", field.increment.map(Value::from));
+    insert("sort_id", field.sort_id.map(Value::from));
+    if !field.raw.is_empty() {
+        insert("raw", Some(Value::Object(field.raw.iter().map(|(k, v)| (k.clone(), v.clone().into())).collect())));
+    }
+    Value::Object(object)
+}
+
+pub(crate) fn paramdef_from_json(json: &Value, path: &str) -> Result<ParamDef, JsonError> {
+    let object = json.as_object().ok_or_else(|| expected(path, "an object"))?;
+    let fields = object.get("fields").and_then(Value::as_array).ok_or_else(|| expected(&member(path, "fields"), "an array"))?;
+    Ok(ParamDef {
+        param_type: string(object, path, "param_type")?.to_owned(),
+        data_version: u32(object, path, "data_version")?,
+        endian: if bool(object, path, "big_endian")? { ParamdefEndian::Big } else { ParamdefEndian::Little },
+        string_format: if bool(object, path, "unicode")? { ParamdefFormat::UTF16 } else { ParamdefFormat::ShiftJIS },
+        format_version: u32(object, path, "format_version")?,
+        fields: fields.iter().enumerate()
+            .map(|(index, field)| field_from_json(field, &member(path, &format!("fields[{}]", index))))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn field_from_json(json: &Value, path: &str) -> Result<ParamField, JsonError> {
+    let object = json.as_object().ok_or_else(|| expected(path, "an object"))?;
+    let field_def = ParamFieldDef::from_str(string(object, path, "def")?)
+        .map_err(|source| JsonError::Def { path: member(path, "def"), source: Box::new(source) })?;
+    let optional_string = |key| optional(object, path, key, "a string", Value::as_str).map(|a| a.map(str::to_owned));
+    let optional_f64 = |key| optional(object, path, key, "a number", Value::as_f64);
+
+    let edit_flags = match object.get("edit_flags") {
+        None => None,
+        Some(edit_flags) => {
+            let flags_path = member(path, "edit_flags");
+            let edit_flags = edit_flags.as_object().ok_or_else(|| expected(&flags_path, "an object"))?;
+            Some(EditFlags { wrap: bool(edit_flags, &flags_path, "wrap")?, lock: bool(edit_flags, &flags_path, "lock")? })
+        }
+    };
+    let printf_format = optional_string("display_format")?;
+
+    Ok(ParamField {
+        field_def,
+        display_name: optional_string("display_name")?,
+        enum_tdf: optional_string("enum")?.map(EnumRef::from),
+        description: optional_string("description")?,
+        descriptions: string_map(object, path, "descriptions")?,
+        display_format: printf_format.as_deref().and_then(|a| DisplayFormat::from_str(a).ok()),
+        printf_format,
+        edit_flags,
+        minimum: optional_f64("minimum")?,
+        maximum: optional_f64("maximum")?,
+        increment: optional_f64("increment")?,
+        sort_id: optional(object, path, "sort_id", "an unsigned integer", Value::as_u64)?.map(|a| a as usize),
+        raw: string_map(object, path, "raw")?,
+    })
+}
+
+/// Reads an optional object of strings, which is empty when absent
+fn string_map(object: &Map<String, Value>, path: &str, key: &str) -> Result<HashMap<String, String>, JsonError> {
+    let Some(map) = object.get(key) else {
+        return Ok(HashMap::new());
+    };
+    let map_path = member(path, key);
+    let map = map.as_object().ok_or_else(|| expected(&map_path, "an object"))?;
+    map.keys().map(|key| Ok((key.clone(), string(map, &map_path, key)?.to_owned()))).collect()
+}
+
+/// The path of a member of the value at `path`, where an empty path is the top level value
+fn member(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_owned(),
+        path => format!("{}.{}", path, key),
+    }
+}
+
+fn expected(path: &str, expected: &'static str) -> JsonError {
+    JsonError::Expected { path: path.to_owned(), expected }
+}
+
+/// Gets an optional member of an object, failing if it is present with the wrong type
+fn optional<'a, T>(object: &'a Map<String, Value>, path: &str, key: &str, description: &'static str, get: impl Fn(&'a Value) -> Option<T>) -> Result<Option<T>, JsonError> {
+    object.get(key).map(|value| get(value).ok_or_else(|| expected(&member(path, key), description))).transpose()
+}
+
+fn string<'a>(object: &'a Map<String, Value>, path: &str, key: &str) -> Result<&'a str, JsonError> {
+    object.get(key).and_then(Value::as_str).ok_or_else(|| expected(&member(path, key), "a string"))
+}
+
+fn u32(object: &Map<String, Value>, path: &str, key: &str) -> Result<u32, JsonError> {
+    object.get(key).and_then(Value::as_u64).and_then(|a| u32::try_from(a).ok())
+        .ok_or_else(|| expected(&member(path, key), "a 32 bit unsigned integer"))
+}
+
+fn bool(object: &Map<String, Value>, path: &str, key: &str) -> Result<bool, JsonError> {
+    object.get(key).and_then(Value::as_bool).ok_or_else(|| expected(&member(path, key), "a boolean"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{EditFlags, Paramdex};
+    use crate::json::JsonError;
+    use crate::test_util::paramdef;
+
+    #[test]
+    fn round_trip() {
+        let mut def = paramdef(&["u8 isEnable:1 = 1", "dummy8 pad:7", "f32 pos[3] = 0.5 // Position", "fixstrW name[16]", "dummy8[4]"]);
+        let field = &mut def.fields[0];
+        field.display_name = Some("Enabled".into());
+        field.enum_tdf = Some("ON_OFF".into());
+        field.descriptions.insert("enUS".into(), "Whether enabled".into());
+        field.printf_format = Some("%d".into());
+        field.display_format = "%d".parse().ok();
+        field.edit_flags = Some(EditFlags { wrap: true, lock: false });
+        field.maximum = Some(1.0);
+        field.sort_id = Some(100);
+        field.raw.insert("Custom".into(), " value ".into());
+        let mut paramdex = Paramdex::empty();
+        paramdex.insert(def);
+
+        let json = paramdex.to_json();
+        assert_eq!(json["TEST_PARAM_ST"]["fields"][2]["def"], "f32 pos[3] = 0.5 // Position");
+        assert!(json["TEST_PARAM_ST"]["fields"][1].get("display_name").is_none());
+        let read = Paramdex::from_json(&json).expect("reads");
+        assert_eq!(read.get_param_def("TEST_PARAM_ST"), paramdex.get_param_def("TEST_PARAM_ST"));
+    }
+
+    #[test]
+    fn errors_locate_value() {
+        let json = json!({"A_PARAM_ST": {
+            "param_type": "A_PARAM_ST", "data_version": 1, "big_endian": false, "unicode": true, "format_version": 203,
+            "fields": [{"def": "u32 a"}, {"def": "u32 b", "minimum": "zero"}],
+        }});
+        match Paramdex::from_json(&json) {
+            Err(JsonError::Expected { path, .. }) => assert_eq!(path, "A_PARAM_ST.fields[1].minimum"),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        let json = json!({"A_PARAM_ST": {
+            "param_type": "A_PARAM_ST", "data_version": 1, "big_endian": false, "unicode": true, "format_version": 203,
+            "fields": [{"def": "u32"}],
+        }});
+        assert!(matches!(Paramdex::from_json(&json), Err(JsonError::Def { path, .. }) if path == "A_PARAM_ST.fields[0].def"));
+    }
+}
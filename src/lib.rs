@@ -11,8 +11,40 @@
 /// [soulsmods/Paramdex](https://github.com/soulsmods/Paramdex).
 pub mod deserialize;
 
-use std::collections::HashMap;
-use crate::deserialize::ParamdefDeserializeError;
+/// Consistency checks for [ParamDef]s, such as duplicate field names.
+pub mod validate;
+
+/// Reading rows of param data laid out according to a [ParamDef].
+pub mod binary;
+
+/// Parsing of the printf(3) style display formats declared on fields.
+pub mod display_format;
+
+/// Comparing the layouts of two versions of a [ParamDef] field by field, and the metadata of fields.
+pub mod diff;
+
+/// Generating source code for structs with the layout of a [ParamDef]'s rows.
+pub mod codegen;
+
+/// Saving a [Paramdex] to a compact binary cache for fast reloading. Requires the `cache` feature.
+#[cfg(feature = "cache")]
+pub mod cache;
+
+/// Converting a [Paramdex] to and from JSON. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod json;
+
+/// Loading a [Paramdex] from a zip archive of defs. Requires the `zip` feature.
+#[cfg(feature = "zip")]
+pub mod archive;
+
+mod hash;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use crate::deserialize::{DefParseError, DeserializeOptions, DeserializeWarning, OnUnknownType, ParamdefDeserializeError};
+use crate::display_format::DisplayFormat;
 
 /// A simple mapping from param type to a [ParamDef]
 pub struct Paramdex {
@@ -36,6 +68,32 @@ impl Paramdex {
         self.definitions.get(key)
     }
 
+    /// Mutably retrieve a [ParamDef] based on a param type, to edit it in place.
+    ///
+    /// The def stays filed under `key` even if its [`ParamDef::param_type`] is changed, so the
+    /// key goes stale. Use [`Paramdex::rekey`] afterwards to file it under its new param type, or
+    /// [`Paramdex::validate_keys`] to find defs with stale keys.
+    pub fn get_param_def_mut(&mut self, key: &str) -> Option<&mut ParamDef> {
+        self.definitions.get_mut(key)
+    }
+
+    /// Files the def under `old_key` under its current [`ParamDef::param_type`] instead, such as
+    /// after renaming it through [`Paramdex::get_param_def_mut`]. Does nothing if the def is
+    /// already filed under its param type.
+    pub fn rekey(&mut self, old_key: &str) -> Result<(), RekeyError> {
+        let new_key = match self.definitions.get(old_key) {
+            Some(paramdef) if paramdef.param_type == old_key => return Ok(()),
+            Some(paramdef) => paramdef.param_type.clone(),
+            None => return Err(RekeyError::NotFound(old_key.into())),
+        };
+        if self.definitions.contains_key(&new_key) {
+            return Err(RekeyError::KeyTaken(new_key));
+        }
+        let paramdef = self.definitions.remove(old_key).expect("def exists");
+        self.definitions.insert(new_key, paramdef);
+        Ok(())
+    }
+
     /// Deserialize a whole Paramdex from an iterator of &str
     pub fn deserialize_all<I: IntoIterator<Item = S>, S: AsRef<str>>(input_iter: I) -> Result<Paramdex, ParamdefDeserializeError> {
         let mut paramdex = Paramdex { definitions: HashMap::new() };
@@ -47,24 +105,126 @@ impl Paramdex {
         Ok(paramdex)
     }
 
+    /// Deserialize a whole Paramdex from an iterator of &str, using the given options for each
+    /// def. Returns the non-fatal quirks found in every def along with the Paramdex, including
+    /// any fields or defs skipped according to [`DeserializeOptions::on_unknown_type`].
+    pub fn deserialize_all_with_options<I: IntoIterator<Item = S>, S: AsRef<str>>(input_iter: I, opts: &DeserializeOptions) -> Result<(Paramdex, Vec<DeserializeWarning>), ParamdefDeserializeError> {
+        let mut paramdex = Paramdex::empty();
+        let mut warnings = Vec::new();
+
+        for input in input_iter {
+            match deserialize::deserialize_def_inner(input.as_ref(), opts, &mut warnings) {
+                Ok(paramdef) => {
+                    paramdex.insert(paramdef);
+                }
+                Err(ParamdefDeserializeError::ParsingDefString(DefParseError::UnrecognizedType(_)))
+                    if opts.on_unknown_type == OnUnknownType::SkipDef => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((paramdex, warnings))
+    }
+
     /// Creates an empty Paramdex.
     pub fn empty() -> Paramdex { Paramdex { definitions: HashMap::new() } }
+
+    /// Creates a Paramdex from defs already keyed by param type. Keys are kept as given, even if
+    /// they differ from a def's [`ParamDef::param_type`]; see [`Paramdex::validate_keys`].
+    pub fn from_map(definitions: HashMap<String, ParamDef>) -> Paramdex { Paramdex { definitions } }
+
+    /// Consumes the Paramdex, returning the backing map from param type to [ParamDef].
+    pub fn into_map(self) -> HashMap<String, ParamDef> { self.definitions }
+
+    /// An approximation of the heap memory used by the Paramdex, in bytes.
+    ///
+    /// This sums the capacities of the strings and vectors held by every def, plus the map's
+    /// entries. Allocator overhead and the map's internal bookkeeping aren't counted, so the
+    /// true footprint is somewhat larger.
+    pub fn approx_heap_size(&self) -> usize {
+        let entries = self.definitions.capacity() * std::mem::size_of::<(String, ParamDef)>();
+        entries + self.definitions.iter()
+            .map(|(key, paramdef)| key.capacity() + paramdef.approx_heap_size())
+            .sum::<usize>()
+    }
+
+    /// Maps each [ParamFieldTypeKind] used in the Paramdex to the param types with at least
+    /// one field of that kind. Param types are listed once each, in sorted order.
+    pub fn type_usage(&self) -> HashMap<ParamFieldTypeKind, Vec<&str>> {
+        let mut usage: HashMap<ParamFieldTypeKind, Vec<&str>> = HashMap::new();
+        for (param_type, paramdef) in &self.definitions {
+            for field in &paramdef.fields {
+                let param_types = usage.entry(field.field_def.field_type.kind()).or_default();
+                if !param_types.contains(&param_type.as_str()) {
+                    param_types.push(param_type);
+                }
+            }
+        }
+        usage.values_mut().for_each(|a| a.sort_unstable());
+        usage
+    }
+
+    /// Finds every field whose [`ParamField::enum_tdf`] is exactly `enum_name`, along with the
+    /// param type of its def. Results are in sorted order of param type, then declaration order.
+    pub fn fields_using_enum(&self, enum_name: &str) -> Vec<(&str, &ParamField)> {
+        let mut param_types: Vec<&String> = self.definitions.keys().collect();
+        param_types.sort_unstable();
+        param_types.into_iter()
+            .flat_map(|param_type| self.definitions[param_type].fields.iter().map(move |field| (param_type.as_str(), field)))
+            .filter(|(_, field)| field.enum_tdf.as_ref().map(EnumRef::name) == Some(enum_name))
+            .collect()
+    }
+
+    /// The union of the internal field names of every def, each listed once however many defs
+    /// use it. Unnamed padding is left out.
+    pub fn all_field_names(&self) -> HashSet<&str> {
+        self.definitions.values()
+            .flat_map(|paramdef| &paramdef.fields)
+            .map(|field| field.field_def.name.as_str())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    /// Calls `f` with each field of every def, along with the def's param type.
+    ///
+    /// Defs are visited in sorted order of param type, and fields in declaration order.
+    pub fn for_each_field<F: FnMut(&str, &ParamField)>(&self, mut f: F) {
+        let _ = self.try_for_each_field(|param_type, field| {
+            f(param_type, field);
+            Ok::<(), std::convert::Infallible>(())
+        });
+    }
+
+    /// Like [`Paramdex::for_each_field`], but stops at and returns the first error from `f`.
+    pub fn try_for_each_field<E, F: FnMut(&str, &ParamField) -> Result<(), E>>(&self, mut f: F) -> Result<(), E> {
+        let mut param_types: Vec<&String> = self.definitions.keys().collect();
+        param_types.sort_unstable();
+        for param_type in param_types {
+            for field in &self.definitions[param_type].fields {
+                f(param_type, field)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// The text format for descriptions in the [ParamDef]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ParamdefFormat {
     UTF16,
     ShiftJIS,
 }
 
 /// The endianness of the specific [ParamDef]
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ParamdefEndian {
     Little,
     Big,
 }
 
 /// A definition for the format of a param file
+///
+/// Equality and hashing compare every member, with floating point values compared bitwise.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct ParamDef {
     /// The internal type key for the parameter
     pub param_type: String,
@@ -75,7 +235,8 @@ pub struct ParamDef {
     /// The endianness declared for the param
     pub endian: ParamdefEndian,
 
-    /// The string encoding declared for the param
+    /// The string encoding declared for the param by its `Unicode` element. This is the encoding
+    /// of the def's own text, and doesn't affect `fixstr` values.
     pub string_format: ParamdefFormat,
 
     /// The version of the format for the XML
@@ -85,31 +246,308 @@ pub struct ParamDef {
     pub fields: Vec<ParamField>
 }
 
+/// The scalar metadata of a [ParamDef], without its fields
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ParamDefHeader {
+    /// The internal type key for the parameter
+    pub param_type: String,
+
+    /// The data version declared for the param
+    pub data_version: u32,
+
+    /// The endianness declared for the param
+    pub endian: ParamdefEndian,
+
+    /// The string encoding declared for the param
+    pub string_format: ParamdefFormat,
+
+    /// The version of the format for the XML
+    pub format_version: u32,
+}
+
+impl Display for ParamDef {
+    /// Summarizes the def in a few lines, without listing fields: the param type, versions,
+    /// encoding, field count and row size. See [`ParamDef::describe`] for a listing of the
+    /// fields.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let endian = match self.endian {
+            ParamdefEndian::Little => "Little endian",
+            ParamdefEndian::Big => "Big endian",
+        };
+        let string_format = match self.string_format {
+            ParamdefFormat::UTF16 => "UTF16",
+            ParamdefFormat::ShiftJIS => "Shift JIS",
+        };
+        writeln!(f, "{} (data version {}, format version {})", self.param_type, self.data_version, self.format_version)?;
+        writeln!(f, "{}, {} strings", endian, string_format)?;
+        write!(f, "{} fields, {} bytes per row", self.fields.len(), self.row_size())
+    }
+}
+
+impl ParamDef {
+    /// Take ownership of the fields, discarding the rest of the def
+    pub fn into_fields(self) -> Vec<ParamField> {
+        self.fields
+    }
+
+    /// A copy of the def's scalar metadata, without its fields
+    pub fn header(&self) -> ParamDefHeader {
+        ParamDefHeader {
+            param_type: self.param_type.clone(),
+            data_version: self.data_version,
+            endian: self.endian,
+            string_format: self.string_format,
+            format_version: self.format_version,
+        }
+    }
+
+    /// A copy of the def under a different param type, such as when forking a param
+    pub fn with_param_type(&self, param_type: impl Into<String>) -> ParamDef {
+        ParamDef { param_type: param_type.into(), ..self.clone() }
+    }
+
+    /// Split the def into its scalar metadata and its fields, without cloning either
+    pub fn into_parts(self) -> (ParamDefHeader, Vec<ParamField>) {
+        let header = ParamDefHeader {
+            param_type: self.param_type,
+            data_version: self.data_version,
+            endian: self.endian,
+            string_format: self.string_format,
+            format_version: self.format_version,
+        };
+        (header, self.fields)
+    }
+
+    /// The number of fields declared in the def
+    pub fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    fn approx_heap_size(&self) -> usize {
+        let string = |a: &Option<String>| a.as_ref().map_or(0, String::capacity);
+        let fields = self.fields.iter().map(|field| {
+            let display_format = field.display_format.as_ref()
+                .map_or(0, |a| a.prefix.capacity() + a.flags.capacity() + a.suffix.capacity());
+            let descriptions = field.descriptions.capacity() * std::mem::size_of::<(String, String)>()
+                + field.descriptions.iter().map(|(tag, text)| tag.capacity() + text.capacity()).sum::<usize>();
+            let raw = field.raw.capacity() * std::mem::size_of::<(String, String)>()
+                + field.raw.iter().map(|(key, value)| key.capacity() + value.capacity()).sum::<usize>();
+            field.field_def.name.capacity() + string(&field.display_name) + field.enum_tdf.as_ref().map_or(0, |a| a.0.capacity())
+                + string(&field.description) + descriptions + string(&field.printf_format) + display_format + raw
+        });
+        self.param_type.capacity() + self.fields.capacity() * std::mem::size_of::<ParamField>() + fields.sum::<usize>()
+    }
+
+    /// Retrieve a field by index, or `None` if out of bounds. Indices follow declaration order,
+    /// which is also the order of the fields within a row.
+    pub fn get_field(&self, index: usize) -> Option<&ParamField> {
+        self.fields.get(index)
+    }
+
+    /// Mutably retrieve a field by index, or `None` if out of bounds. Indices follow declaration
+    /// order, which is also the order of the fields within a row.
+    pub fn get_field_mut(&mut self, index: usize) -> Option<&mut ParamField> {
+        self.fields.get_mut(index)
+    }
+
+    /// Iterates over the fields whose type is the same variant as `ty`, ignoring payloads
+    /// such as bit sizes and lengths. For example, `u32 { bit_size: Some(3) }` matches
+    /// fields of `u32 { bit_size: None }`.
+    pub fn fields_of_type<'a>(&'a self, ty: &ParamFieldType) -> impl Iterator<Item = &'a ParamField> {
+        let kind = ty.kind();
+        self.fields.iter().filter(move |a| a.field_def.field_type.kind() == kind)
+    }
+
+    /// The fields whose internal name matches `pattern`, in declaration order. Requires the
+    /// `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn fields_matching(&self, pattern: &regex::Regex) -> Vec<&ParamField> {
+        self.fields.iter().filter(|a| pattern.is_match(&a.field_def.name)).collect()
+    }
+
+    /// Iterates over the fields that hold data, skipping padding. See [`ParamField::is_dummy`].
+    pub fn data_fields(&self) -> impl Iterator<Item = &ParamField> {
+        self.fields.iter().filter(|a| !a.is_dummy())
+    }
+
+    /// Inserts a field at `index`, shifting later fields back. `index` may equal the field count
+    /// to append. Offsets are computed on demand, so [`ParamDef::field_offsets`] and
+    /// [`ParamDef::row_size`] reflect the change immediately.
+    pub fn insert_field(&mut self, index: usize, field: ParamField) -> Result<(), FieldIndexError> {
+        if index > self.fields.len() {
+            return Err(FieldIndexError { index, len: self.fields.len() });
+        }
+        self.fields.insert(index, field);
+        Ok(())
+    }
+
+    /// Removes and returns the field at `index`, shifting later fields forward, or `None` if
+    /// out of bounds.
+    pub fn remove_field(&mut self, index: usize) -> Option<ParamField> {
+        (index < self.fields.len()).then(|| self.fields.remove(index))
+    }
+
+    /// Length in characters of the longest internal field name, or 0 for a def without fields.
+    pub fn max_field_name_len(&self) -> usize {
+        self.fields.iter().map(|a| a.field_def.name.chars().count()).max().unwrap_or(0)
+    }
+
+    /// Length in characters of the longest display name, considering only fields that declare
+    /// one. Returns 0 if no field has a display name.
+    pub fn max_display_name_len(&self) -> usize {
+        self.fields.iter().filter_map(|a| a.display_name.as_ref()).map(|a| a.chars().count()).max().unwrap_or(0)
+    }
+
+    /// A table of the fields, one per line under a header line, with the columns aligned: the
+    /// index, byte offset in hex, type, bit size, internal name, display name and default of each
+    /// field. Types include the length of strings, arrays and padding, such as `fixstrW[16]`.
+    /// Cells a field doesn't declare are left blank. Columns are aligned by character count, so
+    /// wide characters such as Japanese display names can still misalign them.
+    pub fn describe(&self) -> String {
+        const HEADER: [&str; 7] = ["Index", "Offset", "Type", "Bits", "Name", "Display name", "Default"];
+        let rows: Vec<[String; 7]> = self.fields.iter().zip(self.field_offsets()).enumerate()
+            .map(|(index, (field, offset))| {
+                let field_type = &field.field_def.field_type;
+                let length = match field_type {
+                    ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => Some(*length),
+                    _ => field_type.string_length().or(field.field_def.array_len),
+                };
+                [
+                    index.to_string(),
+                    format!("0x{:x}", offset.byte_offset),
+                    length.map_or_else(|| field_type.token().to_owned(), |a| format!("{}[{}]", field_type.token(), a)),
+                    offset.bits.map(|a| a.size.to_string()).unwrap_or_default(),
+                    field.field_def.name.clone(),
+                    field.display_name.clone().unwrap_or_default(),
+                    field.field_def.default_value.map(|a| a.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        let mut widths = HEADER.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        widths[4] = widths[4].max(self.max_field_name_len());
+        widths[5] = widths[5].max(self.max_display_name_len());
+
+        let line = |cells: [&str; 7]| {
+            let cells: Vec<String> = cells.iter().zip(widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+            cells.join("  ").trim_end().to_owned()
+        };
+        let mut lines = vec![line(HEADER)];
+        lines.extend(rows.iter().map(|row| line(row.each_ref().map(String::as_str))));
+        lines.join("\n")
+    }
+
+    /// Renames the field with internal name `old` to `new`, returning the index of the renamed
+    /// field. Fails without changing anything if there's no field named `old`, or if another
+    /// field is already named `new`, so that names stay unique.
+    pub fn rename_field(&mut self, old: &str, new: &str) -> Result<usize, RenameError> {
+        let index = self.fields.iter().position(|a| a.field_def.name == old)
+            .ok_or_else(|| RenameError::NotFound(old.to_owned()))?;
+        if let Some(existing) = self.fields.iter().enumerate().position(|(i, a)| i != index && a.field_def.name == new) {
+            return Err(RenameError::NameTaken { name: new.to_owned(), index: existing });
+        }
+        self.fields[index].field_def.name = new.to_owned();
+        Ok(index)
+    }
+
+    /// The fields in the order an editor should display them: ascending by
+    /// [`ParamField::sort_id`], with fields that don't declare one after the rest. Ties keep
+    /// declaration order. The def's fields themselves stay in row order.
+    pub fn fields_sorted(&self) -> Vec<&ParamField> {
+        let mut fields: Vec<&ParamField> = self.fields.iter().collect();
+        fields.sort_by_key(|a| (a.sort_id.is_none(), a.sort_id));
+        fields
+    }
+
+    /// Assigns every field a new sort ID, counting up from 0 by `step` in the order of
+    /// [`ParamDef::fields_sorted`], so the editor order is kept while gaps and duplicates are
+    /// removed. Fields without a sort ID are numbered after the rest, in declaration order.
+    ///
+    /// # Panics
+    /// Panics if `step` is 0, or the IDs overflow a `usize`.
+    pub fn renumber_sort_ids(&mut self, step: usize) {
+        assert!(step > 0, "Sort ID step must be positive");
+        let mut order: Vec<usize> = (0..self.fields.len()).collect();
+        order.sort_by_key(|a| (self.fields[*a].sort_id.is_none(), self.fields[*a].sort_id));
+        for (position, index) in order.into_iter().enumerate() {
+            self.fields[index].sort_id = Some(position.checked_mul(step).expect("Sort ID overflow"));
+        }
+    }
+
+    /// Groups fields by the part of their internal name before the first `sep`, such as `atk`
+    /// for `atk_power` with a `_` separator. A field without the separator is grouped under its
+    /// whole name. Fields within a group are in declaration order.
+    pub fn group_by_prefix(&self, sep: char) -> BTreeMap<String, Vec<&ParamField>> {
+        let mut groups: BTreeMap<String, Vec<&ParamField>> = BTreeMap::new();
+        for field in &self.fields {
+            let name = &field.field_def.name;
+            let prefix = name.split_once(sep).map_or(name.as_str(), |(prefix, _)| prefix);
+            groups.entry(prefix.to_owned()).or_default().push(field);
+        }
+        groups
+    }
+}
+
 /// The data type definition for a parameter field
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ParamFieldDef {
     pub field_type: ParamFieldType,
+
+    /// The internal name. Empty for unnamed padding, such as `dummy8[4]` in some older defs.
     pub name: String,
     pub default_value: Option<f64>,
+
+    /// For arrays of a simple type, such as `f32 pos[3]`, the number of elements. The field type
+    /// is then the type of each element. Strings and padding declare their length in their type
+    /// instead, and never set this.
+    pub array_len: Option<usize>,
+
+    /// A trailing `//` comment in the def string, such as `u32 x // tweak later`, without the
+    /// slashes and surrounding whitespace. Ignored by FromSoftware's tools.
+    pub comment: Option<String>,
 }
 
 /// Declared metadata about fields in a param
+#[derive(Clone, Debug)]
 pub struct ParamField {
     /// The definition of the field, including type and internal name, among others.
     pub field_def: ParamFieldDef,
 
     /// A user-friends display name.
+    ///
+    /// Read from the `DisplayName` element, or failing that a `DisplayName` attribute on the
+    /// Field element. See [`DeserializeOptions::display_name_fallbacks`] for other elements.
     pub display_name: Option<String>,
 
     /// A type of enum declared by a paramdex that can be applied to this field. Unused.
-    pub enum_tdf: Option<String>,
+    ///
+    /// This is always a name reference, such as `ON_OFF`, to an enum defined outside the def
+    /// (in Paramdex, the per-game meta files). Defs never declare enum members inline.
+    pub enum_tdf: Option<EnumRef>,
 
     /// A  user-friendly description
     pub description: Option<String>,
 
-    /// A printf(3) compatible format string for printing the data in this field. Unused.
+    /// Localized descriptions, keyed by language tag, for paramdex forks that store them
+    /// alongside the primary [`ParamField::description`].
+    ///
+    /// Any element named `Description` followed by a tag is recognized, such as `DescriptionJP`
+    /// (tag `JP`) or `DescriptionEN` (tag `EN`), as is a `Description` element with a `lang`
+    /// attribute such as `<Description lang="ja">` (tag `ja`). Tags are kept as written, and
+    /// tags of any language are captured.
+    pub descriptions: HashMap<String, String>,
+
+    /// A printf(3) compatible format string for printing the data in this field, as declared.
     pub printf_format: Option<String>,
 
+    /// The parsed form of [`ParamField::printf_format`], if it could be parsed.
+    pub display_format: Option<DisplayFormat>,
+
     /// Flags that inform a potential editor how to handle this field. Unused.
     pub edit_flags: Option<EditFlags>,
 
@@ -122,11 +560,76 @@ pub struct ParamField {
     /// Increment value allowed to be input in an editor. Unused.
     pub increment: Option<f64>,
 
-    /// Declares sorting for a potential editor. Unused.
+    /// Declares sorting for a potential editor. See [`ParamDef::fields_sorted`].
     pub sort_id: Option<usize>,
+
+    /// Every attribute and child element of the Field element as written in the XML, including
+    /// those read into the other members. Only filled with [`DeserializeOptions::keep_raw`], as
+    /// an escape hatch for custom metadata added by forks that this crate doesn't model.
+    ///
+    /// Elements are keyed by tag name, with their text untrimmed, or empty for elements without
+    /// text. Attributes, such as `Def`, are keyed by their name with an `@` prefix. When an
+    /// element appears more than once, the last one is kept.
+    pub raw: HashMap<String, String>,
+}
+
+impl ParamField {
+    /// Whether the field is padding or otherwise unused, which is decided only by the field
+    /// being of type [`ParamFieldType::dummy8`]. Names such as `pad` or `reserve` don't count.
+    pub fn is_dummy(&self) -> bool {
+        matches!(self.field_def.field_type, ParamFieldType::dummy8 { .. })
+    }
+
+    /// The name to show in editors: the display name if declared, otherwise the internal name.
+    pub fn effective_display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.field_def.name)
+    }
+
+    /// The inclusive range editors should clamp values to. A declared minimum or maximum takes
+    /// precedence over the corresponding bound of [`ParamFieldType::value_range`].
+    pub fn effective_range(&self) -> Option<(f64, f64)> {
+        let (minimum, maximum) = self.field_def.field_type.value_range()?;
+        Some((self.minimum.unwrap_or(minimum), self.maximum.unwrap_or(maximum)))
+    }
+
+    /// The lines of the description, or nothing if there is no description.
+    pub fn description_lines(&self) -> impl Iterator<Item = &str> {
+        self.description.iter().flat_map(|a| a.lines())
+    }
+}
+
+/// A reference by name to an enum, as declared by a field's `Enum` element. See
+/// [`ParamField::enum_tdf`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EnumRef(String);
+
+impl EnumRef {
+    /// The name of the referenced enum, such as `ON_OFF`
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for EnumRef {
+    fn from(name: String) -> Self {
+        EnumRef(name)
+    }
+}
+
+impl From<&str> for EnumRef {
+    fn from(name: &str) -> Self {
+        EnumRef(name.into())
+    }
+}
+
+impl Display for EnumRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
 /// Flags used in editors to control user input behavior
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct EditFlags {
     pub wrap: bool,
     pub lock: bool,
@@ -135,12 +638,15 @@ pub struct EditFlags {
 /// Type of field present in the param
 ///
 /// \[su\]\(8\|16\|32\) are integer types, signed and unsigned respectively, with the
-/// appropriate bit sizes.
+/// appropriate bit sizes. Any of them may be declared as a bitfield.
 #[allow(non_camel_case_types)]
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum ParamFieldType {
     /// Signed integer with size of 8 bits
-    s8,
+    s8 {
+        /// Optionally limited to number of bits to be read, sign-extended from the highest bit
+        bit_size: Option<u8>
+    },
 
     /// Unsigned integer with size of 8 bits
     u8 {
@@ -149,7 +655,10 @@ pub enum ParamFieldType {
     },
 
     /// Signed integer with size of 16 bits
-    s16,
+    s16 {
+        /// Optionally limited to number of bits to be read, sign-extended from the highest bit
+        bit_size: Option<u8>
+    },
 
     /// Unsigned integer with size of 16 bits
     u16 {
@@ -158,7 +667,10 @@ pub enum ParamFieldType {
     },
 
     /// Signed integer with size of 32 bits
-    s32,
+    s32 {
+        /// Optionally limited to number of bits to be read, sign-extended from the highest bit
+        bit_size: Option<u8>
+    },
 
     /// Unsigned integer with size of 32 bits
     u32 {
@@ -186,7 +698,7 @@ pub enum ParamFieldType {
 
     /// Fixed-length string encoded in UTF16.
     fixstrW {
-        /// Length of fixed-length string
+        /// Length of fixed-length string, in UTF16 code units (two bytes each)
         length: usize,
     },
 
@@ -197,8 +709,80 @@ pub enum ParamFieldType {
     },
 }
 
+/// The kind of a [ParamFieldType], without any bit size or length payload.
+///
+/// Useful for grouping and comparing field types without destructuring every variant.
+///
+/// Kinds are ordered by the width of a value, narrowest first, and then signed before unsigned,
+/// with `b32`, `f32` and `a32` after the other 32 bit kinds. Strings and padding, whose width
+/// depends on the field, come after every numeric kind in the order `fixstr`, `fixstrW`,
+/// `dummy8`.
+#[allow(non_camel_case_types)]
+// The variant order defines the ordering
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ParamFieldTypeKind {
+    s8,
+    u8,
+    s16,
+    u16,
+    s32,
+    u32,
+    b32,
+    f32,
+    a32,
+    f64,
+    fixstr,
+    fixstrW,
+    dummy8,
+}
+
+/// An error when setting the bit size of a [ParamFieldType]
+#[derive(Error, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BitSizeError {
+    #[error("Bit size not supported on {0:?}")]
+    Unsupported(ParamFieldTypeKind),
+
+    #[error("Bit size {bit_size} is out of range for {kind:?}, which must be between 1 and {max}")]
+    OutOfRange {
+        kind: ParamFieldTypeKind,
+        bit_size: u8,
+        max: u8,
+    },
+}
+
+/// An error when a field index is past the end of a [ParamDef]'s fields
+#[derive(Error, Copy, Clone, Eq, PartialEq, Debug)]
+#[error("Field index {index} is out of bounds for {len} fields")]
+pub struct FieldIndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+/// An error when renaming a field with [`ParamDef::rename_field`]
+#[derive(Error, Clone, Eq, PartialEq, Debug)]
+pub enum RenameError {
+    #[error("No field named {0}")]
+    NotFound(String),
+
+    #[error("Field name {name} is already used by the field at index {index}")]
+    NameTaken {
+        name: String,
+        index: usize,
+    },
+}
+
+/// An error when re-keying a def with [`Paramdex::rekey`]
+#[derive(Error, Clone, Eq, PartialEq, Debug)]
+pub enum RekeyError {
+    #[error("No def is filed under {0}")]
+    NotFound(String),
+
+    #[error("A different def is already filed under {0}")]
+    KeyTaken(String),
+}
+
 /// Enum for type of dummy data
-#[derive(PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum DummyType {
     /// Dummy data is in bytes, with a defined length
     Bytes(usize),
@@ -208,24 +792,564 @@ pub enum DummyType {
 }
 
 impl ParamFieldType {
+    /// A `u8` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 8
+    pub fn u8_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::u8 { bit_size: None }, bit_size)
+    }
+
+    /// A `u16` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 16
+    pub fn u16_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::u16 { bit_size: None }, bit_size)
+    }
+
+    /// A `u32` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 32
+    pub fn u32_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::u32 { bit_size: None }, bit_size)
+    }
+
+    /// An `s8` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 8
+    pub fn s8_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::s8 { bit_size: None }, bit_size)
+    }
+
+    /// An `s16` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 16
+    pub fn s16_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::s16 { bit_size: None }, bit_size)
+    }
+
+    /// An `s32` bitfield of `bit_size` bits, failing unless `bit_size` is between 1 and 32
+    pub fn s32_bits(bit_size: u8) -> Result<Self, BitSizeError> {
+        Self::with_bits(Self::s32 { bit_size: None }, bit_size)
+    }
+
+    fn with_bits(mut field_type: Self, bit_size: u8) -> Result<Self, BitSizeError> {
+        field_type.try_set_bit_size(bit_size)?;
+        Ok(field_type)
+    }
+
     /// Sets the bit size of a field type, on field types that support variable bit lengths.
     ///
     /// # Panics
     /// Panics when the field type does not support bit size definitions. See [`ParamFieldType::supports_bit_size`]
+    ///
+    /// # See also
+    /// [`ParamFieldType::try_set_bit_size`] - For bit sizes that come from user input
     pub fn set_bit_size(&mut self, new_bit_size: u8) {
         match self {
-            Self::u8 {bit_size} | Self::u16 {bit_size} | Self::u32 {bit_size} => {
+            Self::s8 {bit_size} | Self::u8 {bit_size} | Self::s16 {bit_size} | Self::u16 {bit_size}
+            | Self::s32 {bit_size} | Self::u32 {bit_size} => {
                 bit_size.replace(new_bit_size)
             }
             _ => panic!("Bit size not supported"),
         };
     }
 
+    /// Sets the bit size of a field type, returning an error rather than panicking when the type
+    /// doesn't support bit sizes, or when the bit size is zero or wider than the type.
+    pub fn try_set_bit_size(&mut self, new_bit_size: u8) -> Result<(), BitSizeError> {
+        if !self.supports_bit_size() {
+            return Err(BitSizeError::Unsupported(self.kind()));
+        }
+        let max = (self.byte_size() * 8) as u8;
+        if new_bit_size == 0 || new_bit_size > max {
+            return Err(BitSizeError::OutOfRange { kind: self.kind(), bit_size: new_bit_size, max });
+        }
+        self.set_bit_size(new_bit_size);
+        Ok(())
+    }
+
+    /// The kind of this field type, discarding any payload
+    pub fn kind(&self) -> ParamFieldTypeKind {
+        match self {
+            Self::s8 { .. } => ParamFieldTypeKind::s8,
+            Self::u8 { .. } => ParamFieldTypeKind::u8,
+            Self::s16 { .. } => ParamFieldTypeKind::s16,
+            Self::u16 { .. } => ParamFieldTypeKind::u16,
+            Self::s32 { .. } => ParamFieldTypeKind::s32,
+            Self::u32 { .. } => ParamFieldTypeKind::u32,
+            Self::b32 => ParamFieldTypeKind::b32,
+            Self::f32 => ParamFieldTypeKind::f32,
+            Self::a32 => ParamFieldTypeKind::a32,
+            Self::f64 => ParamFieldTypeKind::f64,
+            Self::fixstr { .. } => ParamFieldTypeKind::fixstr,
+            Self::fixstrW { .. } => ParamFieldTypeKind::fixstrW,
+            Self::dummy8 { .. } => ParamFieldTypeKind::dummy8,
+        }
+    }
+
     /// Whether the given field type supports bit size definitions
     pub fn supports_bit_size(&self) -> bool {
+        matches!(self, Self::s8 {..} | Self::u8 {..} | Self::s16 {..} | Self::u16 {..} | Self::s32 {..} | Self::u32 {..})
+    }
+
+    /// Whether values of this type are signed, or `None` for types that aren't numbers: strings,
+    /// padding and `b32`. Floating point types are signed.
+    pub fn is_signed(&self) -> Option<bool> {
+        match self {
+            Self::s8 { .. } | Self::s16 { .. } | Self::s32 { .. } | Self::f32 | Self::a32 | Self::f64 => Some(true),
+            Self::u8 { .. } | Self::u16 { .. } | Self::u32 { .. } => Some(false),
+            Self::b32 | Self::fixstr { .. } | Self::fixstrW { .. } | Self::dummy8 { .. } => None,
+        }
+    }
+
+    /// Whether values of this type are floating point, including `a32` angles
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::f32 | Self::a32 | Self::f64)
+    }
+
+    /// Whether values of this type are strings, `fixstr` or `fixstrW`. A `fixstr` is always
+    /// ShiftJIS and a `fixstrW` always UTF16, whatever the def's [`ParamDef::string_format`].
+    pub fn is_string(&self) -> bool {
+        matches!(self, Self::fixstr { .. } | Self::fixstrW { .. })
+    }
+
+    /// The declared length of a string type, in code units: bytes for `fixstr` and UTF16 code
+    /// units for `fixstrW`. `None` for types that aren't strings.
+    pub fn string_length(&self) -> Option<usize> {
         match self {
-            Self::u8 {..} | Self::u16 {..} | Self::u32 {..} => true,
-            _ => false,
+            Self::fixstr { length } | Self::fixstrW { length } => Some(*length),
+            _ => None,
+        }
+    }
+
+    /// The inclusive range of values this type can represent, for clamping values in fields
+    /// without a declared minimum or maximum. Bitfields are bounded by their bit size rather than
+    /// their base type, and `b32` ranges from 0 to 1. Strings and padding have no range.
+    pub fn value_range(&self) -> Option<(f64, f64)> {
+        let bits = |bit_size: &Option<u8>, width: u8| bit_size.unwrap_or(width) as i32;
+        let signed = |bits: i32| Some((-(2f64.powi(bits - 1)), 2f64.powi(bits - 1) - 1.0));
+        let unsigned = |bits: i32| Some((0.0, 2f64.powi(bits) - 1.0));
+        match self {
+            Self::s8 { bit_size } => signed(bits(bit_size, 8)),
+            Self::s16 { bit_size } => signed(bits(bit_size, 16)),
+            Self::s32 { bit_size } => signed(bits(bit_size, 32)),
+            Self::u8 { bit_size } => unsigned(bits(bit_size, 8)),
+            Self::u16 { bit_size } => unsigned(bits(bit_size, 16)),
+            Self::u32 { bit_size } => unsigned(bits(bit_size, 32)),
+            Self::b32 => Some((0.0, 1.0)),
+            Self::f32 | Self::a32 => Some((f32::MIN as f64, f32::MAX as f64)),
+            Self::f64 => Some((f64::MIN, f64::MAX)),
+            Self::fixstr { .. } | Self::fixstrW { .. } | Self::dummy8 { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::{BitSizeError, FieldIndexError, Paramdex, ParamdefFormat, ParamField, ParamFieldType, ParamFieldTypeKind, RekeyError, RenameError};
+    use crate::test_util::{field, paramdef};
+
+    fn two_param_paramdex() -> Paramdex {
+        let mut paramdex = Paramdex::empty();
+        let mut a = paramdef(&["u32 a", "u32 b:1", "f32 c"]);
+        a.param_type = "A_PARAM_ST".into();
+        let mut b = paramdef(&["f64 a", "f32 b"]);
+        b.param_type = "B_PARAM_ST".into();
+        paramdex.insert(b);
+        paramdex.insert(a);
+        paramdex
+    }
+
+    #[test]
+    fn type_usage() {
+        let paramdex = two_param_paramdex();
+        let usage = paramdex.type_usage();
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[&ParamFieldTypeKind::u32], vec!["A_PARAM_ST"]);
+        assert_eq!(usage[&ParamFieldTypeKind::f32], vec!["A_PARAM_ST", "B_PARAM_ST"]);
+        assert_eq!(usage[&ParamFieldTypeKind::f64], vec!["B_PARAM_ST"]);
+    }
+
+    #[test]
+    fn kind_ignores_payload() {
+        assert_eq!(ParamFieldType::u16 { bit_size: Some(4) }.kind(), ParamFieldTypeKind::u16);
+        assert_eq!(ParamFieldType::fixstrW { length: 16 }.kind(), ParamFieldTypeKind::fixstrW);
+        assert_eq!(ParamFieldType::dummy8 { length: None }.kind(), ParamFieldTypeKind::dummy8);
+        assert_ne!(ParamFieldType::s16 { bit_size: None }.kind(), ParamFieldTypeKind::u16);
+    }
+
+    #[test]
+    fn for_each_field() {
+        let paramdex = two_param_paramdex();
+        let mut visited = Vec::new();
+        paramdex.for_each_field(|param_type, field| visited.push(format!("{}.{}", param_type, field.field_def.name)));
+        assert_eq!(visited, vec!["A_PARAM_ST.a", "A_PARAM_ST.b", "A_PARAM_ST.c", "B_PARAM_ST.a", "B_PARAM_ST.b"]);
+
+        let mut visited = 0;
+        let result = paramdex.try_for_each_field(|param_type, _| {
+            visited += 1;
+            match param_type {
+                "B_PARAM_ST" => Err(param_type.to_owned()),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!(result, Err("B_PARAM_ST".to_owned()));
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn get_field() {
+        let mut def = paramdef(&["u32 a", "f32 b"]);
+        assert_eq!(def.field_count(), 2);
+        assert_eq!(def.get_field(1).map(|a| a.field_def.name.as_str()), Some("b"));
+        assert!(def.get_field(2).is_none());
+        def.get_field_mut(0).expect("field").display_name = Some("A".into());
+        assert_eq!(def.fields[0].display_name.as_deref(), Some("A"));
+        assert!(def.get_field_mut(2).is_none());
+    }
+
+    #[test]
+    fn into_parts() {
+        let (header, fields) = paramdef(&["u32 a", "f32 b"]).into_parts();
+        assert_eq!(header.param_type, "TEST_PARAM_ST");
+        assert_eq!(header.format_version, 203);
+        assert_eq!(fields.len(), 2);
+    }
+
+    #[test]
+    fn try_set_bit_size() {
+        let mut field_type = ParamFieldType::u16 { bit_size: None };
+        assert_eq!(field_type.try_set_bit_size(16), Ok(()));
+        assert_eq!(field_type, ParamFieldType::u16 { bit_size: Some(16) });
+        assert_eq!(
+            field_type.try_set_bit_size(17),
+            Err(BitSizeError::OutOfRange { kind: ParamFieldTypeKind::u16, bit_size: 17, max: 16 })
+        );
+        assert_eq!(
+            field_type.try_set_bit_size(0),
+            Err(BitSizeError::OutOfRange { kind: ParamFieldTypeKind::u16, bit_size: 0, max: 16 })
+        );
+        assert_eq!(field_type, ParamFieldType::u16 { bit_size: Some(16) });
+        assert_eq!(ParamFieldType::f32.try_set_bit_size(1), Err(BitSizeError::Unsupported(ParamFieldTypeKind::f32)));
+    }
+
+    #[test]
+    fn display_paramdef() {
+        let mut def = paramdef(&["s32 refId", "u8 a:1", "u8 b:7", "fixstrW name[4]"]);
+        def.data_version = 3;
+        def.string_format = ParamdefFormat::UTF16;
+        assert_eq!(def.to_string(), "TEST_PARAM_ST (data version 3, format version 203)\nLittle endian, UTF16 strings\n4 fields, 13 bytes per row");
+    }
+
+    #[test]
+    fn describe() {
+        let mut def = paramdef(&["s32 refId = -1", "u8 isEnable:1 = 1", "dummy8 pad:7", "fixstrW name[4]", "f32 pos[3]", "dummy8[2]"]);
+        def.fields[0].display_name = Some("Reference ID".into());
+        def.fields[1].display_name = Some("有効".into());
+        assert_eq!(def.describe(), [
+            "Index  Offset  Type        Bits  Name      Display name  Default",
+            "0      0x0     s32               refId     Reference ID  -1",
+            "1      0x4     u8          1     isEnable  有効            1",
+            "2      0x4     dummy8      7     pad",
+            "3      0x5     fixstrW[4]        name",
+            "4      0xd     f32[3]            pos",
+            "5      0x19    dummy8[2]",
+        ].join("\n"));
+        assert_eq!(paramdef(&[]).describe(), "Index  Offset  Type  Bits  Name  Display name  Default");
+    }
+
+    #[test]
+    fn bits_constructors() {
+        assert_eq!(ParamFieldType::u8_bits(1), Ok(ParamFieldType::u8 { bit_size: Some(1) }));
+        assert_eq!(ParamFieldType::s16_bits(16), Ok(ParamFieldType::s16 { bit_size: Some(16) }));
+        assert_eq!(ParamFieldType::u32_bits(32), Ok(ParamFieldType::u32 { bit_size: Some(32) }));
+        assert_eq!(ParamFieldType::u8_bits(9), Err(BitSizeError::OutOfRange { kind: ParamFieldTypeKind::u8, bit_size: 9, max: 8 }));
+        assert_eq!(ParamFieldType::s32_bits(0), Err(BitSizeError::OutOfRange { kind: ParamFieldTypeKind::s32, bit_size: 0, max: 32 }));
+    }
+
+    #[test]
+    fn data_fields() {
+        let def = paramdef(&["u32 a", "dummy8 pad[4]", "u8 reserve", "dummy8 b:3"]);
+        let names: Vec<&str> = def.data_fields().map(|a| a.field_def.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "reserve"]);
+    }
+
+    #[test]
+    fn approx_heap_size() {
+        assert_eq!(Paramdex::empty().approx_heap_size(), 0);
+
+        let paramdex = two_param_paramdex();
+        let size = paramdex.approx_heap_size();
+        assert!(size > 2 * std::mem::size_of::<ParamField>());
+
+        let mut map = paramdex.into_map();
+        map.get_mut("A_PARAM_ST").expect("present").fields[0].description = Some("A".repeat(1000));
+        assert!(Paramdex::from_map(map).approx_heap_size() >= size + 1000);
+    }
+
+    #[test]
+    fn all_field_names() {
+        let mut paramdex = two_param_paramdex();
+        paramdex.insert(paramdef(&["u32 id", "dummy8[4]"]));
+        assert_eq!(paramdex.all_field_names(), HashSet::from(["a", "b", "c", "id"]));
+        assert!(Paramdex::empty().all_field_names().is_empty());
+    }
+
+    #[test]
+    fn fields_using_enum() {
+        let mut paramdex = Paramdex::empty();
+        for param_type in ["B_PARAM_ST", "A_PARAM_ST"] {
+            let mut def = paramdef(&["u8 a", "u8 b", "u8 c"]);
+            def.param_type = param_type.into();
+            def.fields[0].enum_tdf = Some("ON_OFF".into());
+            def.fields[1].enum_tdf = Some("ON_OFF_2".into());
+            def.fields[2].enum_tdf = Some("ON_OFF".into());
+            paramdex.insert(def);
+        }
+        let uses: Vec<(&str, &str)> = paramdex.fields_using_enum("ON_OFF").into_iter()
+            .map(|(param_type, field)| (param_type, field.field_def.name.as_str()))
+            .collect();
+        assert_eq!(uses, vec![("A_PARAM_ST", "a"), ("A_PARAM_ST", "c"), ("B_PARAM_ST", "a"), ("B_PARAM_ST", "c")]);
+        assert!(paramdex.fields_using_enum("on_off").is_empty());
+    }
+
+    #[test]
+    fn from_and_into_map() {
+        let map = two_param_paramdex().into_map();
+        let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["A_PARAM_ST", "B_PARAM_ST"]);
+
+        let mut map = map;
+        let def = map.remove("B_PARAM_ST").expect("present");
+        map.insert("OTHER".into(), def);
+        let paramdex = Paramdex::from_map(map);
+        assert_eq!(paramdex.get_param_def("OTHER").map(|a| a.param_type.as_str()), Some("B_PARAM_ST"));
+    }
+
+    #[test]
+    fn signedness() {
+        let signedness = |def: &str| {
+            let field_type = field(def).field_def.field_type;
+            (field_type.is_signed(), field_type.is_float())
+        };
+        assert_eq!(signedness("s8 a"), (Some(true), false));
+        assert_eq!(signedness("s16 a:4"), (Some(true), false));
+        assert_eq!(signedness("s32 a"), (Some(true), false));
+        assert_eq!(signedness("u8 a"), (Some(false), false));
+        assert_eq!(signedness("u16 a"), (Some(false), false));
+        assert_eq!(signedness("u32 a:1"), (Some(false), false));
+        assert_eq!(signedness("f32 a"), (Some(true), true));
+        assert_eq!(signedness("a32 a"), (Some(true), true));
+        assert_eq!(signedness("f64 a"), (Some(true), true));
+        assert_eq!(signedness("b32 a"), (None, false));
+        assert_eq!(signedness("fixstr a[4]"), (None, false));
+        assert_eq!(signedness("fixstrW a[4]"), (None, false));
+        assert_eq!(signedness("dummy8 a[4]"), (None, false));
+    }
+
+    #[test]
+    fn strings() {
+        let string = |def: &str| {
+            let field_type = field(def).field_def.field_type;
+            (field_type.is_string(), field_type.string_length())
+        };
+        assert_eq!(string("fixstr a[16]"), (true, Some(16)));
+        assert_eq!(string("fixstrW a[8]"), (true, Some(8)));
+        assert_eq!(string("dummy8 a[4]"), (false, None));
+        assert_eq!(string("u8 a[4]"), (false, None));
+        assert_eq!(string("u32 a"), (false, None));
+    }
+
+    #[test]
+    fn value_ranges() {
+        let range = |def: &str| field(def).field_def.field_type.value_range();
+        assert_eq!(range("s8 a"), Some((-128.0, 127.0)));
+        assert_eq!(range("u8 a"), Some((0.0, 255.0)));
+        assert_eq!(range("u8 a:3"), Some((0.0, 7.0)));
+        assert_eq!(range("s16 a:12"), Some((-2048.0, 2047.0)));
+        assert_eq!(range("u32 a"), Some((0.0, u32::MAX as f64)));
+        assert_eq!(range("s32 a"), Some((i32::MIN as f64, i32::MAX as f64)));
+        assert_eq!(range("u32 a:1"), Some((0.0, 1.0)));
+        assert_eq!(range("b32 a"), Some((0.0, 1.0)));
+        assert_eq!(range("fixstr a[4]"), None);
+        assert_eq!(range("dummy8 a:3"), None);
+    }
+
+    #[test]
+    fn kind_ordering() {
+        assert!(ParamFieldTypeKind::u8 < ParamFieldTypeKind::u16);
+        assert!(ParamFieldTypeKind::u16 < ParamFieldTypeKind::u32);
+        assert!(ParamFieldTypeKind::u32 < ParamFieldTypeKind::f64);
+        assert!(ParamFieldTypeKind::s16 < ParamFieldTypeKind::u16);
+        assert!(ParamFieldTypeKind::f64 < ParamFieldTypeKind::fixstr);
+    }
+
+    #[test]
+    fn insert_and_remove_fields() {
+        let mut def = paramdef(&["u32 a", "u8 c"]);
+        def.insert_field(1, field("f64 b")).expect("in bounds");
+        def.insert_field(3, field("u16 d")).expect("in bounds");
+        assert_eq!(def.insert_field(5, field("u8 e")), Err(FieldIndexError { index: 5, len: 4 }));
+        assert_eq!(def.row_size(), 15);
+        assert_eq!(def.field_offsets()[2].byte_offset, 12);
+
+        assert_eq!(def.remove_field(1).map(|a| a.field_def.name), Some("b".into()));
+        assert!(def.remove_field(3).is_none());
+        assert_eq!(def.row_size(), 7);
+        assert_eq!(def.field_offsets()[1].byte_offset, 4);
+    }
+
+    #[test]
+    fn max_name_lengths() {
+        let mut def = paramdef(&["u32 a", "u32 longest", "u32 mid"]);
+        assert_eq!(def.max_field_name_len(), 7);
+        assert_eq!(def.max_display_name_len(), 0);
+        def.fields[0].display_name = Some("参照ID".into());
+        assert_eq!(def.max_display_name_len(), 4);
+        assert_eq!(paramdef(&[]).max_field_name_len(), 0);
+    }
+
+    #[test]
+    fn rename_field() {
+        let mut def = paramdef(&["u32 a", "u32 b", "u32 c"]);
+        assert_eq!(def.rename_field("b", "renamed"), Ok(1));
+        assert_eq!(def.fields[1].field_def.name, "renamed");
+        assert_eq!(def.rename_field("c", "c"), Ok(2));
+
+        assert_eq!(def.rename_field("b", "d"), Err(RenameError::NotFound("b".into())));
+        assert_eq!(def.rename_field("a", "c"), Err(RenameError::NameTaken { name: "c".into(), index: 2 }));
+        assert_eq!(def.fields[0].field_def.name, "a");
+    }
+
+    #[test]
+    fn with_param_type() {
+        let def = paramdef(&["u32 a", "u8 b:1"]);
+        let mut fork = def.with_param_type("FORK_PARAM_ST");
+        assert_eq!(fork.param_type, "FORK_PARAM_ST");
+        assert_eq!(fork.fields, def.fields);
+
+        fork.fields[0].field_def.name = "renamed".into();
+        assert_eq!(def.fields[0].field_def.name, "a");
+        assert_eq!(def.param_type, "TEST_PARAM_ST");
+    }
+
+    #[test]
+    fn fields_sorted() {
+        let mut def = paramdef(&["u32 a", "u32 b", "u32 c", "u32 d", "u32 e"]);
+        def.fields[0].sort_id = Some(300);
+        def.fields[2].sort_id = Some(100);
+        def.fields[3].sort_id = Some(300);
+        let names: Vec<&str> = def.fields_sorted().iter().map(|a| a.field_def.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "d", "b", "e"]);
+        assert_eq!(def.fields[0].field_def.name, "a");
+    }
+
+    #[test]
+    fn renumber_sort_ids() {
+        let mut def = paramdef(&["u32 a", "u32 b", "u32 c", "u32 d", "u32 e"]);
+        def.fields[0].sort_id = Some(300);
+        def.fields[2].sort_id = Some(100);
+        def.fields[3].sort_id = Some(300);
+        def.renumber_sort_ids(10);
+        let sort_ids: Vec<Option<usize>> = def.fields.iter().map(|a| a.sort_id).collect();
+        assert_eq!(sort_ids, vec![Some(10), Some(30), Some(0), Some(20), Some(40)]);
+
+        let names: Vec<&str> = def.fields_sorted().iter().map(|a| a.field_def.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "d", "b", "e"]);
+        let sorted_ids: Vec<usize> = def.fields_sorted().iter().filter_map(|a| a.sort_id).collect();
+        assert!(sorted_ids.windows(2).all(|a| a[0] < a[1]));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn fields_matching() {
+        let def = paramdef(&["u32 sfxId_0", "u32 other", "u32 sfxId_1", "u32 hitSfxId"]);
+        let names: Vec<&str> = def.fields_matching(&regex::Regex::new("^sfxId").expect("compiles"))
+            .into_iter()
+            .map(|a| a.field_def.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["sfxId_0", "sfxId_1"]);
+    }
+
+    #[test]
+    fn get_param_def_mut() {
+        let mut paramdex = two_param_paramdex();
+        let def = paramdex.get_param_def_mut("A_PARAM_ST").expect("exists");
+        def.rename_field("a", "renamed").expect("renames");
+        def.fields[2].maximum = Some(1.0);
+
+        let def = paramdex.get_param_def("A_PARAM_ST").expect("exists");
+        assert_eq!(def.fields[0].field_def.name, "renamed");
+        assert_eq!(def.fields[2].maximum, Some(1.0));
+        assert!(paramdex.get_param_def_mut("C_PARAM_ST").is_none());
+    }
+
+    #[test]
+    fn rekey() {
+        let mut paramdex = two_param_paramdex();
+        paramdex.get_param_def_mut("A_PARAM_ST").expect("exists").param_type = "C_PARAM_ST".into();
+        assert_eq!(paramdex.rekey("A_PARAM_ST"), Ok(()));
+        assert!(paramdex.get_param_def("A_PARAM_ST").is_none());
+        assert_eq!(paramdex.get_param_def("C_PARAM_ST").expect("rekeyed").fields.len(), 3);
+        assert!(paramdex.validate_keys().is_empty());
+
+        assert_eq!(paramdex.rekey("C_PARAM_ST"), Ok(()));
+        assert_eq!(paramdex.rekey("A_PARAM_ST"), Err(RekeyError::NotFound("A_PARAM_ST".into())));
+
+        paramdex.get_param_def_mut("B_PARAM_ST").expect("exists").param_type = "C_PARAM_ST".into();
+        assert_eq!(paramdex.rekey("B_PARAM_ST"), Err(RekeyError::KeyTaken("C_PARAM_ST".into())));
+        assert!(paramdex.get_param_def("B_PARAM_ST").is_some());
+    }
+
+    #[test]
+    fn group_by_prefix() {
+        let def = paramdef(&["u32 atk_power", "u32 atkSuper_power", "u32 atk_speed", "u32 hp", "u32 atkSuper_speed"]);
+        let groups: Vec<(String, Vec<&str>)> = def.group_by_prefix('_').into_iter()
+            .map(|(prefix, fields)| (prefix, fields.iter().map(|a| a.field_def.name.as_str()).collect()))
+            .collect();
+        assert_eq!(groups, vec![
+            ("atk".to_owned(), vec!["atk_power", "atk_speed"]),
+            ("atkSuper".to_owned(), vec!["atkSuper_power", "atkSuper_speed"]),
+            ("hp".to_owned(), vec!["hp"]),
+        ]);
+    }
+
+    #[test]
+    fn fields_of_type_ignores_payload() {
+        let def = paramdef(&["u32 a:3", "s32 b", "u32 c", "u8 d:1"]);
+        let names: Vec<&str> = def.fields_of_type(&ParamFieldType::u32 { bit_size: None })
+            .map(|a| a.field_def.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "c"]);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use crate::{ParamDef, ParamdefEndian, ParamdefFormat, ParamField, ParamFieldDef};
+
+    /// Builds a field from a def string, with no other metadata
+    pub fn field(def: &str) -> ParamField {
+        ParamField {
+            field_def: ParamFieldDef::from_str(def).expect("parses"),
+            display_name: None,
+            enum_tdf: None,
+            description: None,
+            descriptions: HashMap::new(),
+            printf_format: None,
+            display_format: None,
+            edit_flags: None,
+            minimum: None,
+            maximum: None,
+            increment: None,
+            sort_id: None,
+            raw: HashMap::new(),
+        }
+    }
+
+    /// Builds a little endian, ShiftJIS def from a list of def strings
+    pub fn paramdef(defs: &[&str]) -> ParamDef {
+        ParamDef {
+            param_type: "TEST_PARAM_ST".into(),
+            data_version: 1,
+            endian: ParamdefEndian::Little,
+            string_format: ParamdefFormat::ShiftJIS,
+            format_version: 203,
+            fields: defs.iter().map(|a| field(a)).collect(),
         }
     }
 }
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use thiserror::Error;
+use crate::binary::FieldValue;
+use crate::{DummyType, Paramdex, ParamDef, ParamFieldType};
+
+/// How serious a [ValidationError] is.
+///
+/// Warnings describe defs that load fine but are likely mistakes, and can be filtered out
+/// by callers that only care about hard errors.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Severity {
+    /// Likely a mistake, but the def is still usable
+    Warning,
+
+    /// The def cannot be used correctly as declared
+    Error,
+}
+
+/// A problem found when validating a [ParamDef]
+#[derive(Error, Clone, PartialEq, Debug)]
+pub enum ValidationError {
+    #[error("Field name {name} is declared more than once, at indices {indices:?}")]
+    DuplicateFieldName {
+        /// The duplicated internal field name
+        name: String,
+
+        /// The indices of every field declared with this name
+        indices: Vec<usize>,
+    },
+
+    #[error("Sort ID {sort_id} is declared by more than one field, at indices {indices:?}")]
+    DuplicateSortId {
+        /// The duplicated sort ID
+        sort_id: usize,
+
+        /// The indices of every field declared with this sort ID
+        indices: Vec<usize>,
+    },
+
+    #[error("Field {name} has a minimum of {minimum}, above its maximum of {maximum}")]
+    InvertedRange {
+        /// The internal name of the field
+        name: String,
+        minimum: f64,
+        maximum: f64,
+    },
+
+    #[error("Field {name} has an increment of {increment}, which must be positive")]
+    NonPositiveIncrement {
+        /// The internal name of the field
+        name: String,
+        increment: f64,
+    },
+
+    #[error("Field name {name} is not a valid C or Rust identifier, consider {suggestion}")]
+    NonIdentifierFieldName {
+        /// The internal field name as declared
+        name: String,
+
+        /// A sanitized form of the name that is a valid identifier, see
+        /// [`ParamField::sanitized_name`](crate::ParamField::sanitized_name)
+        suggestion: String,
+    },
+
+    #[error("Padding fields at indices {indices:?} could be merged into one dummy8 of {bytes} bytes")]
+    MergeableDummies {
+        /// The indices of the adjacent byte padding fields
+        indices: Range<usize>,
+
+        /// The combined size of the padding fields, in bytes
+        bytes: usize,
+    },
+
+    #[error("Bit group at indices {indices:?} leaves {unused_bits} bits of its storage unit unused, and field {next} starts a new unit")]
+    UnclosedBitGroup {
+        /// The indices of the bitfields sharing the storage unit
+        indices: Range<usize>,
+
+        /// The number of bits left over at the top of the storage unit
+        unused_bits: u8,
+
+        /// The internal name of the bitfield after the group, which could not be packed into its
+        /// storage unit
+        next: String,
+    },
+
+    #[error("String field {name} has invalid length {length}")]
+    InvalidFixstrLength {
+        /// The internal name of the string field
+        name: String,
+
+        /// The declared length of the string
+        length: usize,
+    },
+}
+
+/// A value in a decoded row that violates its field's declared constraints. See
+/// [`ParamDef::validate_row`].
+#[derive(Error, Clone, PartialEq, Debug)]
+pub enum RowValidationError {
+    #[error("Row has {actual} values, but the def has {expected} fields")]
+    ValueCount {
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Field {name} has value {value}, below its minimum of {minimum}")]
+    BelowMinimum {
+        /// The internal name of the field
+        name: String,
+        value: f64,
+        minimum: f64,
+    },
+
+    #[error("Field {name} has value {value}, above its maximum of {maximum}")]
+    AboveMaximum {
+        /// The internal name of the field
+        name: String,
+        value: f64,
+        maximum: f64,
+    },
+}
+
+impl ValidationError {
+    /// The [Severity] of this validation error.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::DuplicateFieldName { .. } => Severity::Warning,
+            Self::DuplicateSortId { .. } => Severity::Warning,
+            Self::NonIdentifierFieldName { .. } => Severity::Warning,
+            Self::MergeableDummies { .. } => Severity::Warning,
+            Self::UnclosedBitGroup { .. } => Severity::Warning,
+            Self::InvalidFixstrLength { .. } => Severity::Error,
+            Self::InvertedRange { .. } => Severity::Error,
+            Self::NonPositiveIncrement { .. } => Severity::Error,
+        }
+    }
+}
+
+impl ParamDef {
+    /// Checks the def for internal inconsistencies, returning every problem found.
+    ///
+    /// An empty result means the def passed all checks. Use [`ValidationError::severity`]
+    /// to filter out warning-level lints.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        check_duplicate_field_names(self, &mut errors);
+        check_duplicate_sort_ids(self, &mut errors);
+        check_identifiers(self, &mut errors);
+        check_mergeable_dummies(self, &mut errors);
+        check_bit_groups(self, &mut errors);
+        check_fixstr_lengths(self, &mut errors);
+        check_ranges(self, &mut errors);
+        errors
+    }
+}
+
+impl ParamDef {
+    /// Checks each numeric value of a decoded row, such as from [`ParamDef::read_row`], against
+    /// its field's declared [`minimum`](crate::ParamField::minimum) and
+    /// [`maximum`](crate::ParamField::maximum), returning every violation in declaration order.
+    /// Each element of an array is checked.
+    ///
+    /// Enum membership is not checked, as enum definitions aren't part of a def.
+    pub fn validate_row(&self, values: &[FieldValue]) -> Vec<RowValidationError> {
+        if values.len() != self.fields.len() {
+            return vec![RowValidationError::ValueCount { expected: self.fields.len(), actual: values.len() }];
+        }
+
+        let mut errors = Vec::new();
+        for (field, value) in self.fields.iter().zip(values) {
+            let elements = match value {
+                FieldValue::Array(elements) => elements.as_slice(),
+                value => std::slice::from_ref(value),
+            };
+            for value in elements.iter().filter_map(FieldValue::as_f64) {
+                let name = &field.field_def.name;
+                if let Some(minimum) = field.minimum.filter(|a| value < *a) {
+                    errors.push(RowValidationError::BelowMinimum { name: name.clone(), value, minimum });
+                }
+                if let Some(maximum) = field.maximum.filter(|a| value > *a) {
+                    errors.push(RowValidationError::AboveMaximum { name: name.clone(), value, maximum });
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl Paramdex {
+    /// Finds defs filed under a key other than their own [`ParamDef::param_type`], which can
+    /// happen with [`Paramdex::from_map`]. Returns `(key, param_type)` pairs sorted by key; an
+    /// empty result means every def is filed under its param type.
+    pub fn validate_keys(&self) -> Vec<(String, String)> {
+        let mut mismatches: Vec<(String, String)> = self.definitions.iter()
+            .filter(|(key, paramdef)| **key != paramdef.param_type)
+            .map(|(key, paramdef)| (key.clone(), paramdef.param_type.clone()))
+            .collect();
+        mismatches.sort_unstable();
+        mismatches
+    }
+}
+
+fn check_duplicate_field_names(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    let mut indices_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    // Unnamed padding is exempt, as it can't be referred to by name
+    for (index, field) in paramdef.fields.iter().enumerate().filter(|(_, a)| !a.field_def.name.is_empty()) {
+        indices_by_name.entry(field.field_def.name.as_str()).or_default().push(index);
+    }
+
+    let mut duplicates: Vec<(&str, Vec<usize>)> = indices_by_name.into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .collect();
+    duplicates.sort_by_key(|(_, indices)| indices[0]);
+
+    errors.extend(duplicates.into_iter().map(|(name, indices)| ValidationError::DuplicateFieldName {
+        name: name.to_owned(),
+        indices,
+    }));
+}
+
+/// Only fields that declare a sort ID participate. Editors order fields by sort ID, so
+/// duplicates leave the displayed order up to the editor.
+fn check_duplicate_sort_ids(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    let mut indices_by_sort_id: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, field) in paramdef.fields.iter().enumerate() {
+        if let Some(sort_id) = field.sort_id {
+            indices_by_sort_id.entry(sort_id).or_default().push(index);
+        }
+    }
+
+    let mut duplicates: Vec<(usize, Vec<usize>)> = indices_by_sort_id.into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .collect();
+    duplicates.sort_by_key(|(_, indices)| indices[0]);
+
+    errors.extend(duplicates.into_iter().map(|(sort_id, indices)| ValidationError::DuplicateSortId { sort_id, indices }));
+}
+
+/// Names are legal in a def, but not in generated code. Keywords are not flagged, as code
+/// generation already escapes them, and neither is unnamed padding, which is given a name.
+fn check_identifiers(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    for field in &paramdef.fields {
+        let name = &field.field_def.name;
+        if !name.is_empty() && !is_identifier(name) {
+            errors.push(ValidationError::NonIdentifierFieldName { name: name.clone(), suggestion: field.sanitized_name() });
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|a| a.is_ascii_alphabetic() || a == '_')
+        && chars.all(|a| a.is_ascii_alphanumeric() || a == '_')
+}
+
+/// Only byte padding is merged, as bit padding is bounded by its storage unit
+fn check_mergeable_dummies(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    let padding_bytes = |index: usize| match paramdef.fields.get(index)?.field_def.field_type {
+        ParamFieldType::dummy8 { length: None } => Some(1),
+        ParamFieldType::dummy8 { length: Some(DummyType::Bytes(length)) } => Some(length),
+        _ => None,
+    };
+
+    let mut index = 0;
+    while index < paramdef.fields.len() {
+        let start = index;
+        let mut bytes = 0;
+        while let Some(length) = padding_bytes(index) {
+            bytes += length;
+            index += 1;
+        }
+        if index - start > 1 {
+            errors.push(ValidationError::MergeableDummies { indices: start..index, bytes });
+        }
+        index = index.max(start + 1);
+    }
+}
+
+/// A bitfield can't pack into the previous group's unit when its storage kind differs, such as
+/// `u8 a:4` followed by `u16 b:4`, or when it doesn't fit in the remaining bits. It then starts a
+/// new unit, leaving the rest of the previous unit as implicit padding. Groups that fill their unit,
+/// or are followed by a field that isn't a bitfield, are fine.
+fn check_bit_groups(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    let offsets = paramdef.field_offsets();
+    let mut index = 0;
+    while index < offsets.len() {
+        let start = index;
+        let mut used = 0;
+        while let Some(bits) = offsets.get(index).filter(|a| a.byte_offset == offsets[start].byte_offset).and_then(|a| a.bits) {
+            used = bits.offset + bits.size;
+            index += 1;
+        }
+        if index == start {
+            index += 1;
+            continue;
+        }
+        let unit_bits = (offsets[start].byte_size * 8) as u8;
+        if let Some(next) = paramdef.fields.get(index).filter(|_| offsets[index].bits.is_some() && used < unit_bits) {
+            errors.push(ValidationError::UnclosedBitGroup { indices: start..index, unused_bits: unit_bits - used, next: next.field_def.name.clone() });
+        }
+    }
+}
+
+/// Strings must hold at least one character. `fixstrW` lengths count UTF16 code units rather
+/// than bytes, so any nonzero length is whole.
+fn check_fixstr_lengths(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    for field in &paramdef.fields {
+        match field.field_def.field_type {
+            ParamFieldType::fixstr { length: 0 } | ParamFieldType::fixstrW { length: 0 } => {
+                errors.push(ValidationError::InvalidFixstrLength {
+                    name: field.field_def.name.clone(),
+                    length: 0,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Editors clamp values to the minimum and maximum, and step by the increment
+fn check_ranges(paramdef: &ParamDef, errors: &mut Vec<ValidationError>) {
+    for field in &paramdef.fields {
+        let name = &field.field_def.name;
+        if let (Some(minimum), Some(maximum)) = (field.minimum, field.maximum) {
+            if minimum > maximum {
+                errors.push(ValidationError::InvertedRange { name: name.clone(), minimum, maximum });
+            }
+        }
+        if let Some(increment) = field.increment.filter(|a| *a <= 0.0) {
+            errors.push(ValidationError::NonPositiveIncrement { name: name.clone(), increment });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::{Paramdex, ParamFieldType};
+    use crate::test_util::paramdef;
+    use crate::binary::FieldValue;
+    use crate::validate::{RowValidationError, Severity, ValidationError};
+
+    #[test]
+    fn no_duplicates() {
+        assert!(paramdef(&["u32 a", "u32 b"]).validate().is_empty());
+    }
+
+    #[test]
+    fn duplicate_field_names() {
+        let errors = paramdef(&["u32 unk1", "u8 b", "s16 unk1", "f32 b", "u8 unk1"]).validate();
+        assert_eq!(errors, vec![
+            ValidationError::DuplicateFieldName { name: "unk1".into(), indices: vec![0, 2, 4] },
+            ValidationError::DuplicateFieldName { name: "b".into(), indices: vec![1, 3] },
+        ]);
+        assert!(errors.iter().all(|a| a.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn unnamed_padding() {
+        assert!(paramdef(&["u8 a", "dummy8[3]", "u16 b", "dummy8[2]"]).validate().is_empty());
+    }
+
+    #[test]
+    fn duplicate_sort_ids() {
+        let mut def = paramdef(&["u32 a", "u32 b", "u32 c", "u32 d", "u32 e", "u32 f"]);
+        for (index, sort_id) in [(0, 200), (1, 100), (3, 200), (4, 100), (5, 200)] {
+            def.fields[index].sort_id = Some(sort_id);
+        }
+        let errors = def.validate();
+        assert_eq!(errors, vec![
+            ValidationError::DuplicateSortId { sort_id: 200, indices: vec![0, 3, 5] },
+            ValidationError::DuplicateSortId { sort_id: 100, indices: vec![1, 4] },
+        ]);
+        assert!(errors.iter().all(|a| a.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn mismatched_keys() {
+        let mut map = HashMap::new();
+        map.insert("TEST_PARAM_ST".to_owned(), paramdef(&["u32 a"]));
+        map.insert("WRONG_PARAM_ST".to_owned(), paramdef(&["u32 a"]));
+        map.insert("ALSO_WRONG_PARAM_ST".to_owned(), paramdef(&["u32 a"]));
+        assert_eq!(Paramdex::from_map(map).validate_keys(), vec![
+            ("ALSO_WRONG_PARAM_ST".to_owned(), "TEST_PARAM_ST".to_owned()),
+            ("WRONG_PARAM_ST".to_owned(), "TEST_PARAM_ST".to_owned()),
+        ]);
+
+        let mut paramdex = Paramdex::empty();
+        paramdex.insert(paramdef(&["u32 a"]));
+        assert!(paramdex.validate_keys().is_empty());
+    }
+
+    #[test]
+    fn ranges() {
+        let mut def = paramdef(&["u8 a", "u8 b", "u8 c", "u8 d"]);
+        (def.fields[0].minimum, def.fields[0].maximum, def.fields[0].increment) = (Some(0.0), Some(10.0), Some(0.5));
+        (def.fields[1].minimum, def.fields[1].maximum) = (Some(10.0), Some(0.0));
+        def.fields[2].increment = Some(0.0);
+        (def.fields[3].minimum, def.fields[3].maximum, def.fields[3].increment) = (Some(1.0), Some(1.0), Some(-1.0));
+        assert_eq!(def.validate(), vec![
+            ValidationError::InvertedRange { name: "b".into(), minimum: 10.0, maximum: 0.0 },
+            ValidationError::NonPositiveIncrement { name: "c".into(), increment: 0.0 },
+            ValidationError::NonPositiveIncrement { name: "d".into(), increment: -1.0 },
+        ]);
+    }
+
+    #[test]
+    fn non_identifier_field_names() {
+        let mut def = paramdef(&["u32 valid_Name1", "u32 private", "u32 ｇradFactor", "u32 sfx", "u32 ok"]);
+        def.fields[1].field_def.name = "_private".into();
+        def.fields[3].field_def.name = "2nd sfx".into();
+        def.fields[4].field_def.name = "a-b".into();
+        let errors = def.validate();
+        assert_eq!(errors, vec![
+            ValidationError::NonIdentifierFieldName { name: "ｇradFactor".into(), suggestion: "gradFactor".into() },
+            ValidationError::NonIdentifierFieldName { name: "2nd sfx".into(), suggestion: "_2nd_sfx".into() },
+            ValidationError::NonIdentifierFieldName { name: "a-b".into(), suggestion: "a_b".into() },
+        ]);
+        assert!(errors.iter().all(|a| a.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn mergeable_dummies() {
+        assert!(paramdef(&["dummy8 a", "u8 b", "dummy8 c[2]", "dummy8 d:4", "dummy8 e:4"]).validate().is_empty());
+        let errors = paramdef(&["dummy8 a", "dummy8 b[3]", "u8 c", "dummy8 d[2]", "dummy8 e:8", "dummy8 f", "dummy8 g", "dummy8 h"]).validate();
+        assert_eq!(errors, vec![
+            ValidationError::MergeableDummies { indices: 0..2, bytes: 4 },
+            ValidationError::MergeableDummies { indices: 5..8, bytes: 3 },
+        ]);
+        assert!(errors.iter().all(|a| a.severity() == Severity::Warning));
+        assert_eq!(errors[0].to_string(), "Padding fields at indices 0..2 could be merged into one dummy8 of 4 bytes");
+    }
+
+    #[test]
+    fn unclosed_bit_groups() {
+        // A full u8 unit, then a u8 unit closed by a u16 bitfield, then a u16 unit closed by one
+        // that doesn't fit, and finally a u16 unit closed by a field that isn't a bitfield
+        let def = paramdef(&["u8 a:1", "u8 b:7", "u8 c:4", "u16 d:4", "u16 e:10", "u16 f:8", "u16 g:4", "f32 h"]);
+        let errors = def.validate();
+        assert_eq!(errors, vec![
+            ValidationError::UnclosedBitGroup { indices: 2..3, unused_bits: 4, next: "d".into() },
+            ValidationError::UnclosedBitGroup { indices: 3..5, unused_bits: 2, next: "f".into() },
+        ]);
+        assert!(errors.iter().all(|a| a.severity() == Severity::Warning));
+
+        assert!(paramdef(&["u8 a:4", "dummy8 pad:4", "u16 b:4", "u16 pad2:12"]).validate().is_empty());
+    }
+
+    #[test]
+    fn validate_row() {
+        let mut def = paramdef(&["s32 a", "f32 b", "u8 c[3]", "fixstr d[4]"]);
+        (def.fields[0].minimum, def.fields[0].maximum) = (Some(-1.0), Some(100.0));
+        def.fields[1].minimum = Some(0.0);
+        def.fields[2].maximum = Some(10.0);
+
+        let row = def.read_row(&[
+            0xFE, 0xFF, 0xFF, 0xFF,
+            0, 0, 0x80, 0x3F,
+            5, 11, 20,
+            b'a', 0, 0, 0,
+        ]).expect("reads");
+        assert_eq!(def.validate_row(&row), vec![
+            RowValidationError::BelowMinimum { name: "a".into(), value: -2.0, minimum: -1.0 },
+            RowValidationError::AboveMaximum { name: "c".into(), value: 11.0, maximum: 10.0 },
+            RowValidationError::AboveMaximum { name: "c".into(), value: 20.0, maximum: 10.0 },
+        ]);
+
+        assert!(def.validate_row(&def.default_row()).is_empty());
+        assert_eq!(def.validate_row(&[FieldValue::S32(0)]), vec![RowValidationError::ValueCount { expected: 4, actual: 1 }]);
+    }
+
+    #[test]
+    fn fixstr_lengths() {
+        assert!(paramdef(&["fixstr a[16]", "fixstrW b[7]"]).validate().is_empty());
+        // The parser rejects zero lengths, but defs built in code can still have them
+        let mut def = paramdef(&["fixstr a[1]", "fixstrW b[1]"]);
+        def.fields[0].field_def.field_type = ParamFieldType::fixstr { length: 0 };
+        def.fields[1].field_def.field_type = ParamFieldType::fixstrW { length: 0 };
+        assert_eq!(
+            def.validate(),
+            vec![
+                ValidationError::InvalidFixstrLength { name: "a".into(), length: 0 },
+                ValidationError::InvalidFixstrLength { name: "b".into(), length: 0 },
+            ]
+        );
+    }
+}